@@ -0,0 +1,3 @@
+-- Add due date to TODOs
+
+ALTER TABLE "todos" ADD COLUMN "due_date" TEXT;
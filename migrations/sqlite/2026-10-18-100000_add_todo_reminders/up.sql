@@ -0,0 +1,15 @@
+-- Add personal TODO reminders
+
+CREATE TABLE IF NOT EXISTS "todo_reminders"
+(
+    "id"            INTEGER PRIMARY KEY NOT NULL,
+    "channel_id"    BIGINT              NOT NULL,
+    "todo_id"       INTEGER             NOT NULL,
+    "guild_id"      BIGINT,
+    "requester"     BIGINT              NOT NULL,
+    "remind_date"   TEXT                NOT NULL,
+    "creation_date" TEXT                NOT NULL,
+
+    FOREIGN KEY ("channel_id", "todo_id") REFERENCES "todos" ("channel_id", "id")
+        ON UPDATE CASCADE ON DELETE CASCADE
+);
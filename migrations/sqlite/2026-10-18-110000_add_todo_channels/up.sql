@@ -0,0 +1,11 @@
+-- Add per-channel TODO settings, NULL values fall back to the bot settings
+
+CREATE TABLE IF NOT EXISTS "todo_channels"
+(
+    "channel_id"          BIGINT PRIMARY KEY NOT NULL,
+    "reminders_enabled"   BOOLEAN,
+    "reminder_interval"   INTEGER,
+    "reminder_time"       TEXT,
+    "reminder_quiet_days" TEXT,
+    "last_reminder_date"  TEXT
+);
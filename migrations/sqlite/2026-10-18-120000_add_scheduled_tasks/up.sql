@@ -0,0 +1,9 @@
+-- Add state of scheduled tasks
+
+CREATE TABLE IF NOT EXISTS "scheduled_tasks"
+(
+    "name"       TEXT PRIMARY KEY NOT NULL,
+    "last_run"   TEXT,
+    "next_run"   TEXT,
+    "last_error" TEXT
+);
@@ -0,0 +1,14 @@
+-- Add checklist items to TODOs
+
+CREATE TABLE IF NOT EXISTS "todo_checklist_items"
+(
+    "channel_id" BIGINT  NOT NULL,
+    "todo_id"    INTEGER NOT NULL,
+    "id"         INTEGER NOT NULL,
+    "text"       TEXT    NOT NULL,
+    "checked"    BOOLEAN NOT NULL DEFAULT 0,
+
+    PRIMARY KEY ("channel_id", "todo_id", "id"),
+    FOREIGN KEY ("channel_id", "todo_id") REFERENCES "todos" ("channel_id", "id")
+        ON UPDATE CASCADE ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX IF EXISTS "todo_tags_guild_id";
+DROP TABLE IF EXISTS "todo_tags";
@@ -0,0 +1,15 @@
+-- Add tags to TODOs
+
+CREATE TABLE IF NOT EXISTS "todo_tags"
+(
+    "channel_id" BIGINT  NOT NULL,
+    "todo_id"    INTEGER NOT NULL,
+    "guild_id"   BIGINT  NOT NULL,
+    "tag"        TEXT    NOT NULL,
+
+    PRIMARY KEY ("channel_id", "todo_id", "tag"),
+    FOREIGN KEY ("channel_id", "todo_id") REFERENCES "todos" ("channel_id", "id")
+        ON UPDATE CASCADE ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS "todo_tags_guild_id" ON "todo_tags" ("guild_id");
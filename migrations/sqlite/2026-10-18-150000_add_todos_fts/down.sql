@@ -0,0 +1,6 @@
+-- Remove full-text search index of TODOs
+
+DROP TRIGGER IF EXISTS "todos_fts_update";
+DROP TRIGGER IF EXISTS "todos_fts_delete";
+DROP TRIGGER IF EXISTS "todos_fts_insert";
+DROP TABLE IF EXISTS "todos_fts";
@@ -0,0 +1,37 @@
+-- Add full-text search index of TODOs
+
+CREATE VIRTUAL TABLE IF NOT EXISTS "todos_fts" USING fts5
+(
+    "todo",
+    "channel_id" UNINDEXED,
+    "todo_id" UNINDEXED,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO "todos_fts" ("todo", "channel_id", "todo_id")
+SELECT "todo", "channel_id", "id"
+FROM "todos";
+
+CREATE TRIGGER IF NOT EXISTS "todos_fts_insert"
+    AFTER INSERT
+    ON "todos"
+BEGIN
+    INSERT INTO "todos_fts" ("todo", "channel_id", "todo_id")
+    VALUES (new."todo", new."channel_id", new."id");
+END;
+
+CREATE TRIGGER IF NOT EXISTS "todos_fts_delete"
+    AFTER DELETE
+    ON "todos"
+BEGIN
+    DELETE FROM "todos_fts" WHERE "channel_id" = old."channel_id" AND "todo_id" = old."id";
+END;
+
+CREATE TRIGGER IF NOT EXISTS "todos_fts_update"
+    AFTER UPDATE OF "channel_id", "id", "todo"
+    ON "todos"
+BEGIN
+    DELETE FROM "todos_fts" WHERE "channel_id" = old."channel_id" AND "todo_id" = old."id";
+    INSERT INTO "todos_fts" ("todo", "channel_id", "todo_id")
+    VALUES (new."todo", new."channel_id", new."id");
+END;
@@ -0,0 +1,3 @@
+-- Remove history of changes to TODOs
+
+DROP TABLE IF EXISTS "todo_events";
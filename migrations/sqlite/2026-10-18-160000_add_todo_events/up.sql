@@ -0,0 +1,15 @@
+-- Add history of changes to TODOs
+
+CREATE TABLE IF NOT EXISTS "todo_events"
+(
+    "id"            INTEGER PRIMARY KEY NOT NULL,
+    "channel_id"    BIGINT              NOT NULL,
+    "todo_id"       INTEGER             NOT NULL,
+    "actor"         BIGINT              NOT NULL,
+    "action"        TEXT                NOT NULL,
+    "old_value"     TEXT,
+    "new_value"     TEXT,
+    "creation_date" TEXT                NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS "todo_events_todo" ON "todo_events" ("channel_id", "todo_id");
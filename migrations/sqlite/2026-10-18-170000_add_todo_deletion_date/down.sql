@@ -0,0 +1,5 @@
+-- Remove soft deletion of TODOs
+
+DELETE FROM "todos" WHERE "deletion_date" IS NOT NULL;
+
+ALTER TABLE "todos" DROP COLUMN "deletion_date";
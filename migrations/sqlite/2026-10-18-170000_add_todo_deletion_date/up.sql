@@ -0,0 +1,3 @@
+-- Add soft deletion of TODOs
+
+ALTER TABLE "todos" ADD COLUMN "deletion_date" TEXT;
@@ -0,0 +1,3 @@
+-- Add recurrence rule to TODOs
+
+ALTER TABLE "todos" ADD COLUMN "recurrence" TEXT;
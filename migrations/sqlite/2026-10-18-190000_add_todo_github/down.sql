@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX "todos_channel_issue";
+
+ALTER TABLE "todos" DROP COLUMN "issue";
+
+DROP TABLE "todo_github_repos";
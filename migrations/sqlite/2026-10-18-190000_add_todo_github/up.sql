@@ -0,0 +1,12 @@
+-- Link channels to GitHub repositories and TODOs to their issues
+
+CREATE TABLE IF NOT EXISTS "todo_github_repos"
+(
+    "channel_id"     BIGINT PRIMARY KEY NOT NULL,
+    "repository"     TEXT               NOT NULL,
+    "last_sync_date" TEXT
+);
+
+ALTER TABLE "todos" ADD COLUMN "issue" INTEGER;
+
+CREATE UNIQUE INDEX IF NOT EXISTS "todos_channel_issue" ON "todos" ("channel_id", "issue");
@@ -0,0 +1,8 @@
+-- Add pinned TODO boards
+
+CREATE TABLE IF NOT EXISTS "todo_boards"
+(
+    "channel_id"    BIGINT PRIMARY KEY NOT NULL,
+    "message_id"    BIGINT             NOT NULL,
+    "last_event_id" INTEGER
+);
@@ -0,0 +1,12 @@
+-- Allocate TODO IDs in the database
+
+CREATE TABLE IF NOT EXISTS "todo_sequences"
+(
+    "channel_id" BIGINT PRIMARY KEY NOT NULL,
+    "next_id"    INTEGER            NOT NULL
+);
+
+INSERT INTO "todo_sequences" ("channel_id", "next_id")
+SELECT "channel_id", MAX("id") + 1
+FROM "todos"
+GROUP BY "channel_id";
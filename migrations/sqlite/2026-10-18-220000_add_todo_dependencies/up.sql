@@ -0,0 +1,17 @@
+-- Add blocking relationships between TODOs
+
+CREATE TABLE IF NOT EXISTS "todo_dependencies"
+(
+    "channel_id"         BIGINT  NOT NULL,
+    "todo_id"            INTEGER NOT NULL,
+    "blocker_channel_id" BIGINT  NOT NULL,
+    "blocker_id"         INTEGER NOT NULL,
+
+    PRIMARY KEY ("channel_id", "todo_id", "blocker_channel_id", "blocker_id"),
+    FOREIGN KEY ("channel_id", "todo_id") REFERENCES "todos" ("channel_id", "id")
+        ON UPDATE CASCADE ON DELETE CASCADE,
+    FOREIGN KEY ("blocker_channel_id", "blocker_id") REFERENCES "todos" ("channel_id", "id")
+        ON UPDATE CASCADE ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS "todo_dependencies_blocker" ON "todo_dependencies" ("blocker_channel_id", "blocker_id");
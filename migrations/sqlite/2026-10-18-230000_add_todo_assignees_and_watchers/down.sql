@@ -0,0 +1,15 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE IF EXISTS "todo_watchers";
+
+ALTER TABLE "todos" ADD COLUMN "assignee" BIGINT;
+
+-- Only one of the assignees can be kept
+UPDATE "todos"
+SET "assignee" = (SELECT MIN("assignee_id")
+                  FROM "todo_assignees"
+                  WHERE "todo_assignees"."channel_id" = "todos"."channel_id"
+                    AND "todo_assignees"."todo_id" = "todos"."id");
+
+DROP INDEX IF EXISTS "todo_assignees_assignee_id";
+DROP TABLE IF EXISTS "todo_assignees";
@@ -0,0 +1,33 @@
+-- Allow multiple assignees and watchers of TODOs
+
+CREATE TABLE IF NOT EXISTS "todo_assignees"
+(
+    "channel_id"  BIGINT  NOT NULL,
+    "todo_id"     INTEGER NOT NULL,
+    "assignee_id" BIGINT  NOT NULL,
+
+    PRIMARY KEY ("channel_id", "todo_id", "assignee_id"),
+    FOREIGN KEY ("channel_id", "todo_id") REFERENCES "todos" ("channel_id", "id")
+        ON UPDATE CASCADE ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS "todo_assignees_assignee_id" ON "todo_assignees" ("assignee_id");
+
+INSERT INTO "todo_assignees" ("channel_id", "todo_id", "assignee_id")
+SELECT "channel_id", "id", "assignee"
+FROM "todos"
+WHERE "assignee" IS NOT NULL;
+
+ALTER TABLE "todos" DROP COLUMN "assignee";
+
+CREATE TABLE IF NOT EXISTS "todo_watchers"
+(
+    "channel_id"    BIGINT  NOT NULL,
+    "todo_id"       INTEGER NOT NULL,
+    "user_id"       BIGINT  NOT NULL,
+    "last_event_id" INTEGER NOT NULL DEFAULT 0,
+
+    PRIMARY KEY ("channel_id", "todo_id", "user_id"),
+    FOREIGN KEY ("channel_id", "todo_id") REFERENCES "todos" ("channel_id", "id")
+        ON UPDATE CASCADE ON DELETE CASCADE
+);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+DELETE FROM "todo_assignees" WHERE "is_role";
+
+ALTER TABLE "todo_assignees" DROP COLUMN "is_role";
@@ -0,0 +1,3 @@
+-- Assignees can be roles too, their IDs don't collide with user IDs
+
+ALTER TABLE "todo_assignees" ADD COLUMN "is_role" BOOLEAN NOT NULL DEFAULT 0;
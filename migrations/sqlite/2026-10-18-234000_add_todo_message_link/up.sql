@@ -0,0 +1,3 @@
+-- Link TODOs created from messages to the message
+
+ALTER TABLE "todos" ADD COLUMN "message_link" TEXT;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE "todo_channels" DROP COLUMN "create_threads";
+
+ALTER TABLE "todos" DROP COLUMN "thread_id";
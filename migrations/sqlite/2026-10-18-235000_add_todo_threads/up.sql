@@ -0,0 +1,5 @@
+-- Threads for discussing TODOs, optionally started for every new TODO in the channel
+
+ALTER TABLE "todos" ADD COLUMN "thread_id" BIGINT;
+
+ALTER TABLE "todo_channels" ADD COLUMN "create_threads" BOOLEAN;
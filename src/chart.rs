@@ -0,0 +1,168 @@
+//! Line chart rendered to PNG with `plotters`, the font is embedded as there
+//! are no system fonts in the runtime image.
+
+use std::{io::Cursor, sync::Once};
+
+use image::{ImageFormat, RgbImage};
+use plotters::{
+    prelude::*,
+    style::{register_font, FontStyle},
+};
+use tracing::warn;
+
+use crate::Result;
+
+pub const RED: RGBColor = RGBColor(0xed, 0x42, 0x45);
+
+// Colors matching the dark Discord theme
+const BACKGROUND: RGBColor = RGBColor(0x31, 0x33, 0x38);
+const GRID: RGBColor = RGBColor(0x44, 0x46, 0x4d);
+const AXIS: RGBColor = RGBColor(0xb5, 0xba, 0xc1);
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 400;
+const MARGIN: u32 = 24;
+const LABEL_AREA: u32 = 40;
+
+const FONT: &str = "sans-serif";
+const FONT_SIZE: u32 = 14;
+static FONT_DATA: &[u8] = include_bytes!("../assets/fonts/DejaVuSans.ttf");
+static REGISTER_FONT: Once = Once::new();
+
+const GRID_LINES: usize = 4;
+const MAX_LABELS: usize = 8;
+
+/// Values of a single line, one for each of the chart labels.
+pub struct Series<'a> {
+    pub values: &'a [u32],
+    pub color: RGBColor,
+}
+
+/// Renders the series as lines over the labels, returns PNG image.
+pub fn line_chart(labels: &[String], series: &[Series<'_>]) -> Result<Vec<u8>> {
+    REGISTER_FONT.call_once(|| {
+        if register_font(FONT, FontStyle::Normal, FONT_DATA).is_err() {
+            warn!("Embedded chart font is invalid");
+        }
+    });
+
+    let max = series
+        .iter()
+        .flat_map(|s| s.values.iter().copied())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    // Single point is drawn as a flat line over the whole chart
+    let last = labels.len().saturating_sub(1).max(1);
+
+    let mut buffer = vec![0; WIDTH as usize * HEIGHT as usize * 3];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (WIDTH, HEIGHT)).into_drawing_area();
+        root.fill(&BACKGROUND)?;
+
+        let mut chart = ChartBuilder::on(&root)
+            .margin(MARGIN)
+            .x_label_area_size(LABEL_AREA)
+            .y_label_area_size(LABEL_AREA)
+            .build_cartesian_2d(0..last, 0..max)?;
+
+        let label_style = (FONT, FONT_SIZE).into_font().color(&AXIS);
+        chart
+            .configure_mesh()
+            .disable_x_mesh()
+            .bold_line_style(GRID)
+            .light_line_style(BACKGROUND)
+            .axis_style(AXIS)
+            .label_style(label_style)
+            .x_labels(MAX_LABELS)
+            .x_label_formatter(&|i| labels.get(*i).cloned().unwrap_or_default())
+            .y_labels(GRID_LINES + 1)
+            .draw()?;
+
+        for s in series {
+            let points: Vec<(usize, u32)> = match s.values {
+                [value] => vec![(0, *value), (last, *value)],
+                values => values.iter().copied().enumerate().collect(),
+            };
+            // Lines are two pixels thick to be visible in scaled down previews
+            chart.draw_series(LineSeries::new(points, s.color.stroke_width(2)))?;
+        }
+
+        root.present()?;
+    }
+
+    let image = RgbImage::from_raw(WIDTH, HEIGHT, buffer).expect("buffer matches the size");
+    let mut png = Cursor::new(vec![]);
+    image.write_to(&mut png, ImageFormat::Png)?;
+    Ok(png.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgb;
+
+    use super::*;
+
+    fn decode(png: &[u8]) -> RgbImage {
+        image::load_from_memory_with_format(png, ImageFormat::Png)
+            .unwrap()
+            .into_rgb8()
+    }
+
+    fn labels(count: usize) -> Vec<String> {
+        (1..=count).map(|day| format!("05-{day:02}")).collect()
+    }
+
+    fn has_color(image: &RgbImage, color: RGBColor) -> bool {
+        let color = Rgb([color.0, color.1, color.2]);
+        image.pixels().any(|pixel| *pixel == color)
+    }
+
+    #[test]
+    fn line_chart_is_valid_png() {
+        let values = [0, 3, 1, 4, 1, 5, 9];
+        let png = line_chart(
+            &labels(values.len()),
+            &[Series {
+                values: &values,
+                color: RED,
+            }],
+        )
+        .unwrap();
+
+        let image = decode(&png);
+        assert_eq!(image.dimensions(), (WIDTH, HEIGHT));
+        assert_eq!(*image.get_pixel(0, 0), Rgb([0x31, 0x33, 0x38]));
+        assert!(has_color(&image, RED));
+        assert!(has_color(&image, AXIS));
+    }
+
+    #[test]
+    fn line_chart_with_wide_labels() {
+        let values = [1_000_000_000, 0];
+        let png = line_chart(
+            &labels(values.len()),
+            &[Series {
+                values: &values,
+                color: RED,
+            }],
+        )
+        .unwrap();
+
+        assert_eq!(decode(&png).dimensions(), (WIDTH, HEIGHT));
+    }
+
+    #[test]
+    fn line_chart_single_value() {
+        let png = line_chart(
+            &labels(1),
+            &[Series {
+                values: &[2],
+                color: RED,
+            }],
+        )
+        .unwrap();
+
+        assert!(has_color(&decode(&png), RED));
+    }
+}
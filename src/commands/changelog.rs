@@ -1,11 +1,9 @@
-use octocrab::Octocrab;
-
 use crate::{Context, Result, VERSION};
 
 /// Get the latest changelog
 #[poise::command(slash_command)]
 pub async fn changelog(ctx: Context<'_>) -> Result<()> {
-    let octocrab = Octocrab::builder().build()?;
+    let octocrab = ctx.data().settings.github.client()?;
     let release = &octocrab
         .repos("seqre", "secubot")
         .releases()
@@ -1,7 +1,7 @@
 use std::sync::LazyLock;
 
 use regex::Regex;
-use time::{format_description, format_description::FormatItem};
+use time::{format_description, format_description::FormatItem, OffsetDateTime, PrimitiveDateTime};
 
 use crate::{Context, Result};
 
@@ -13,13 +13,25 @@ pub mod ping;
 #[allow(clippy::cast_sign_loss)]
 pub mod todo;
 
+pub const DISCORD_EMBED_DESCRIPTION_LIMIT: usize = 4096;
 pub const DISCORD_EMBED_FIELDS_LIMIT: u32 = 24;
+pub const DISCORD_EMBED_FIELD_VALUE_LIMIT: usize = 1024;
+pub const DISCORD_SELECT_OPTION_LABEL_LIMIT: usize = 100;
+pub const DISCORD_CHANNEL_NAME_LIMIT: usize = 100;
 
 static USER_PING_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<@(\d+)>").unwrap());
-static TIME_FORMAT: LazyLock<Vec<FormatItem<'static>>> = LazyLock::new(|| {
+pub static TIME_FORMAT: LazyLock<Vec<FormatItem<'static>>> = LazyLock::new(|| {
     format_description::parse("[year]-[month]-[day] [hour]:[minute]:[second]").unwrap()
 });
 
+/// Parses date stored in the database in [`TIME_FORMAT`], all dates are in
+/// UTC.
+pub fn parse_time(date: &str) -> Option<OffsetDateTime> {
+    PrimitiveDateTime::parse(date, &TIME_FORMAT)
+        .ok()
+        .map(PrimitiveDateTime::assume_utc)
+}
+
 #[poise::command(track_edits, slash_command)]
 pub async fn help(
     ctx: Context<'_>,
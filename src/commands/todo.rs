@@ -4,36 +4,103 @@
 #![allow(clippy::cast_possible_truncation)]
 
 use std::{
-    collections::HashMap,
-    sync::{
-        atomic::{AtomicI32, Ordering},
-        Mutex,
-    },
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::{Arc, LazyLock},
     time::Duration,
 };
 
 use diesel::{
     prelude::*,
-    result::{Error::NotFound, QueryResult},
+    result::{
+        DatabaseErrorKind::UniqueViolation,
+        Error::{DatabaseError, NotFound},
+        QueryResult,
+    },
+    sql_types::{BigInt, Text},
 };
 use itertools::Itertools;
+use octocrab::models::IssueState;
 use poise::{
     async_trait,
     serenity_prelude::{
-        json, ButtonStyle, ChannelId, GuildChannel, Member, MessageBuilder, UserId,
+        json, ButtonStyle, CacheHttp, ChannelId, ChannelType, CreateComponents, CreateEmbed, Guild,
+        GuildChannel, GuildId, Http, Member, Mentionable, Message, MessageBuilder,
+        MessageComponentInteraction, Permissions, UserId,
     },
     SlashArgument,
 };
-use time::OffsetDateTime;
-use tokio_stream::{self as stream, StreamExt};
+use time::{
+    format_description::{self, FormatItem},
+    OffsetDateTime, Time, Weekday,
+};
+use tokio::sync::RwLock;
+use tokio_stream as stream;
 use tracing::debug;
 
+use self::{
+    board::pin_board,
+    dependency::blocker,
+    github::github,
+    selection::{format_ids, Assignee, AssigneeSelection, IdSelection},
+    stats::stats,
+    thread::threads,
+    transfer::{export, import},
+    watch::{unwatch, watch},
+};
+pub use self::{
+    board::update_boards, digest::send_digests, github::sync_issues, message::todo_from_message,
+    watch::notify_watchers,
+};
 use crate::{
-    commands::{DISCORD_EMBED_FIELDS_LIMIT, TIME_FORMAT},
-    models::todo::{NewTodo, Todo},
+    commands::{
+        parse_time, DISCORD_EMBED_DESCRIPTION_LIMIT, DISCORD_EMBED_FIELDS_LIMIT,
+        DISCORD_EMBED_FIELD_VALUE_LIMIT, DISCORD_SELECT_OPTION_LABEL_LIMIT, TIME_FORMAT,
+    },
+    ctx_data::CtxData,
+    models::todo::{
+        ChannelSettings, ChecklistItem, Event, NewChannelSettings, NewChecklistItem, NewEvent,
+        NewReminder, NewTag, NewTodo, SearchMatch, Todo,
+    },
+    settings::TodoReminders,
+    tasks::cron::{self, Schedule},
     utils, Conn, Context, Result,
 };
 
+mod board;
+mod dependency;
+mod digest;
+mod github;
+mod message;
+mod selection;
+mod stats;
+mod thread;
+mod transfer;
+mod watch;
+
+// How long deleted TODO can be restored with the button
+const UNDO_TIMEOUT: Duration = Duration::from_secs(60 * 5);
+
+// How long modals creating and editing TODOs wait for submission
+const MODAL_TIMEOUT: Duration = Duration::from_secs(60 * 10);
+
+// Shown when the allocated TODO ID is taken anyway, e.g. by an external insert
+// done outside of the sequence
+const ID_CONFLICT: &str = "TODO with the same ID already exists, try again.";
+
+// Number of moved or copied TODOs whose new IDs are listed
+const MAX_LISTED_TRANSFERS: usize = 50;
+
+// Number of most recent changes shown by `/todo history`
+const HISTORY_LIMIT: usize = 20;
+
+// Values of changes longer than that are shortened, edits store whole texts
+const MAX_CHANGE_VALUE_LENGTH: usize = 100;
+
+static DUE_DATE_FORMAT: LazyLock<Vec<FormatItem<'static>>> = LazyLock::new(|| {
+    format_description::parse("[year]-[month]-[day] [hour]:[minute] UTC").unwrap()
+});
+
 #[derive(Debug, Clone, Copy, Default)]
 pub enum Priority {
     #[default]
@@ -101,73 +168,389 @@ impl SlashArgument for Priority {
     }
 }
 
+/// Kind of change recorded in the TODO history.
+#[derive(Debug, Clone, Copy)]
+enum TodoAction {
+    Created,
+    Edited,
+    ChangedDueDate,
+    ChangedRecurrence,
+    Assigned,
+    ChangedPriority,
+    Moved,
+    Completed,
+    Uncompleted,
+    Deleted,
+    Restored,
+    AddedChecklistItem,
+    CheckedChecklistItem,
+    UncheckedChecklistItem,
+    RemovedChecklistItem,
+    Tagged,
+    Untagged,
+    Copied,
+    AddedBlocker,
+    RemovedBlocker,
+}
+
+impl TodoAction {
+    /// Whether the values of the action are mentions, which shouldn't be
+    /// escaped when displayed.
+    fn has_mentions(self) -> bool {
+        matches!(
+            self,
+            TodoAction::Assigned
+                | TodoAction::Moved
+                | TodoAction::Copied
+                | TodoAction::AddedBlocker
+                | TodoAction::RemovedBlocker
+        )
+    }
+}
+
+impl std::fmt::Display for TodoAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        let action = match self {
+            TodoAction::Created => "created",
+            TodoAction::Edited => "edited",
+            TodoAction::ChangedDueDate => "changed_due_date",
+            TodoAction::ChangedRecurrence => "changed_recurrence",
+            TodoAction::Assigned => "assigned",
+            TodoAction::ChangedPriority => "changed_priority",
+            TodoAction::Moved => "moved",
+            TodoAction::Completed => "completed",
+            TodoAction::Uncompleted => "uncompleted",
+            TodoAction::Deleted => "deleted",
+            TodoAction::Restored => "restored",
+            TodoAction::AddedChecklistItem => "added_checklist_item",
+            TodoAction::CheckedChecklistItem => "checked_checklist_item",
+            TodoAction::UncheckedChecklistItem => "unchecked_checklist_item",
+            TodoAction::RemovedChecklistItem => "removed_checklist_item",
+            TodoAction::Tagged => "tagged",
+            TodoAction::Untagged => "untagged",
+            TodoAction::Copied => "copied",
+            TodoAction::AddedBlocker => "added_blocker",
+            TodoAction::RemovedBlocker => "removed_blocker",
+        };
+        write!(f, "{action}")
+    }
+}
+
+impl FromStr for TodoAction {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "created" => Ok(TodoAction::Created),
+            "edited" => Ok(TodoAction::Edited),
+            "changed_due_date" => Ok(TodoAction::ChangedDueDate),
+            "changed_recurrence" => Ok(TodoAction::ChangedRecurrence),
+            "assigned" => Ok(TodoAction::Assigned),
+            "changed_priority" => Ok(TodoAction::ChangedPriority),
+            "moved" => Ok(TodoAction::Moved),
+            "completed" => Ok(TodoAction::Completed),
+            "uncompleted" => Ok(TodoAction::Uncompleted),
+            "deleted" => Ok(TodoAction::Deleted),
+            "restored" => Ok(TodoAction::Restored),
+            "added_checklist_item" => Ok(TodoAction::AddedChecklistItem),
+            "checked_checklist_item" => Ok(TodoAction::CheckedChecklistItem),
+            "unchecked_checklist_item" => Ok(TodoAction::UncheckedChecklistItem),
+            "removed_checklist_item" => Ok(TodoAction::RemovedChecklistItem),
+            "tagged" => Ok(TodoAction::Tagged),
+            "untagged" => Ok(TodoAction::Untagged),
+            "copied" => Ok(TodoAction::Copied),
+            "added_blocker" => Ok(TodoAction::AddedBlocker),
+            "removed_blocker" => Ok(TodoAction::RemovedBlocker),
+            _ => Err(()),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 struct TodoEntry {
+    channel_id: ChannelId,
     id: i32,
-    assignee: Option<String>,
+    assignees: Vec<String>,
     text: String,
     completed: bool,
     priority: i32,
+    due_date: Option<OffsetDateTime>,
+    deletion_date: Option<OffsetDateTime>,
+    recurrence: Option<String>,
+    issue: Option<i32>,
+    message_link: Option<String>,
+    thread: Option<ChannelId>,
+    checklist: Option<ChecklistProgress>,
+    tags: Vec<String>,
+    // Channel IDs and IDs of uncompleted TODOs blocking this one
+    blocked_by: Vec<(i64, i32)>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+struct ChecklistProgress {
+    checked: usize,
+    total: usize,
 }
 
 impl TodoEntry {
-    pub async fn new(
-        id: i32,
-        assignee: Option<i64>,
-        text: String,
-        completed: bool,
-        priority: i32,
-        ctx: Context<'_>,
+    pub async fn new(todo: Todo, assignees: &[Assignee], ctx: Context<'_>) -> Self {
+        Self::in_guild(todo, assignees, ctx, ctx.guild_id()).await
+    }
+
+    /// Creates entry outside of a command, the assignees are looked up among
+    /// members and roles of `guild`, without a guild they are mentioned.
+    async fn in_guild(
+        todo: Todo,
+        assignees: &[Assignee],
+        cache_http: impl CacheHttp,
+        guild: Option<GuildId>,
     ) -> Self {
-        let assignee = match assignee {
-            Some(id) => {
-                let userid = UserId(id as u64);
-                let guildid = ctx.guild_id().unwrap();
-                let member = guildid.member(ctx, userid).await;
-                member.ok().map(|m| utils::get_nick_from_member(&m))
-            }
-            None => None,
-        };
-        let completed = completed | false;
+        let mut names = vec![];
+        for &assignee in assignees {
+            let Some(guild) = guild else {
+                names.push(assignee.mention().to_string());
+                continue;
+            };
+            if let Some(name) = get_assignee_name(&cache_http, guild, assignee).await {
+                names.push(name);
+            }
+        }
+        let completed = todo.completion_date.is_some();
+        let due_date = todo.due_date.as_deref().and_then(parse_time);
+        let deletion_date = todo.deletion_date.as_deref().and_then(parse_time);
         Self {
-            id,
-            assignee,
-            text,
+            channel_id: ChannelId(todo.channel_id as u64),
+            id: todo.id,
+            assignees: names,
+            text: todo.todo,
             completed,
-            priority,
+            priority: todo.priority,
+            due_date,
+            deletion_date,
+            recurrence: todo.recurrence,
+            issue: todo.issue,
+            message_link: todo.message_link,
+            thread: todo.thread_id.map(|thread| ChannelId(thread as u64)),
+            checklist: None,
+            tags: vec![],
+            blocked_by: vec![],
+        }
+    }
+
+    fn is_overdue(&self, now: OffsetDateTime) -> bool {
+        !self.completed && self.due_date.is_some_and(|due| due < now)
+    }
+}
+
+fn parse_due_date(input: &str) -> Option<String> {
+    utils::parse_date(input, OffsetDateTime::now_utc()).map(|due| due.format(&TIME_FORMAT).unwrap())
+}
+
+/// Rule by which completed TODO is added again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Recurrence {
+    Daily,
+    Weekly,
+    // Day of the month occurrences fall on, taken from the first due date
+    // when not known yet, so that shorter months don't move it
+    Monthly(Option<u8>),
+    Cron(Schedule),
+}
+
+impl Recurrence {
+    /// Returns due date of the next occurrence, which is always in the future.
+    /// Intervals are counted from the previous due date to keep the time of
+    /// the day, cron schedules are followed as they are.
+    fn next_due(&self, due: Option<OffsetDateTime>, now: OffsetDateTime) -> Option<OffsetDateTime> {
+        let base = due.unwrap_or(now);
+
+        let advance = |date: OffsetDateTime| match self {
+            Recurrence::Daily => Some(date + time::Duration::days(1)),
+            Recurrence::Weekly => Some(date + time::Duration::weeks(1)),
+            Recurrence::Monthly(day) => utils::add_month(date, day.unwrap_or(base.day())),
+            Recurrence::Cron(schedule) => schedule.next_after(date),
+        };
+
+        let mut next = advance(base)?;
+        while next <= now {
+            next = advance(next)?;
+        }
+        Some(next)
+    }
+
+    /// Returns the recurrence with the day of the month fixed to the one of
+    /// `due`, other recurrences are returned unchanged.
+    fn anchored(&self, due: OffsetDateTime) -> Self {
+        match self {
+            Recurrence::Monthly(None) => Recurrence::Monthly(Some(due.day())),
+            recurrence => recurrence.clone(),
+        }
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = cron::ParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "daily" => Ok(Recurrence::Daily),
+            "weekly" => Ok(Recurrence::Weekly),
+            "monthly" => Ok(Recurrence::Monthly(None)),
+            other => {
+                let day = other
+                    .strip_prefix("monthly on day ")
+                    .and_then(|day| day.parse().ok())
+                    .filter(|day| (1..=31).contains(day));
+                match day {
+                    Some(day) => Ok(Recurrence::Monthly(Some(day))),
+                    None => s.parse().map(Recurrence::Cron),
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            Recurrence::Daily => write!(f, "daily"),
+            Recurrence::Weekly => write!(f, "weekly"),
+            Recurrence::Monthly(None) => write!(f, "monthly"),
+            Recurrence::Monthly(Some(day)) => write!(f, "monthly on day {day}"),
+            Recurrence::Cron(schedule) => write!(f, "{schedule}"),
+        }
+    }
+}
+
+/// Reminder schedule of a channel, channel settings take precedence over
+/// the bot settings.
+#[derive(Debug)]
+pub struct ReminderSchedule {
+    pub enabled: bool,
+    pub interval: u32,
+    pub time: Time,
+    pub quiet_days: HashSet<Weekday>,
+}
+
+impl ReminderSchedule {
+    pub fn new(
+        settings: &TodoReminders,
+        channel: ChannelId,
+        overrides: Option<&ChannelSettings>,
+    ) -> Self {
+        let enabled = overrides
+            .and_then(|o| o.reminders_enabled)
+            .unwrap_or_else(|| !settings.disabled_channels.contains(&channel));
+        let interval = overrides
+            .and_then(|o| o.reminder_interval)
+            .map_or(settings.interval, |i| i as u32);
+        let time = overrides
+            .and_then(|o| o.reminder_time.as_deref())
+            .and_then(utils::parse_time_of_day)
+            .unwrap_or(settings.time);
+        let quiet_days = overrides
+            .and_then(|o| o.reminder_quiet_days.as_deref())
+            .and_then(utils::parse_weekdays)
+            .map_or_else(|| settings.quiet_days.clone(), HashSet::from_iter);
+
+        Self {
+            enabled,
+            interval,
+            time,
+            quiet_days,
+        }
+    }
+
+    /// Checks whether reminder should be sent at `now`, given the date of the
+    /// last one.
+    pub fn is_due(&self, now: OffsetDateTime, last: Option<OffsetDateTime>) -> bool {
+        self.enabled
+            && !self.quiet_days.contains(&now.weekday())
+            && now.time() >= self.time
+            && last
+                .is_none_or(|last| (now.date() - last.date()).whole_days() >= self.interval.into())
+    }
+}
+
+impl std::fmt::Display for ReminderSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        if !self.enabled {
+            return write!(f, "disabled");
+        }
+
+        write!(
+            f,
+            "every {} day(s) at {} UTC",
+            self.interval,
+            utils::format_time_of_day(self.time)
+        )?;
+
+        if !self.quiet_days.is_empty() {
+            write!(f, ", except {}", utils::format_weekdays(&self.quiet_days))?;
         }
+
+        Ok(())
     }
 }
 
 #[derive(Debug)]
 pub struct TodoData {
-    iterators: Mutex<HashMap<ChannelId, AtomicI32>>,
+    tags: RwLock<HashMap<GuildId, HashSet<String>>>,
 }
 
 impl TodoData {
     pub fn new(db: &Conn) -> Self {
-        use crate::schema::todos::dsl::todos;
+        let tags = Self::load_tags(&mut db.get().unwrap()).unwrap();
+
+        Self {
+            tags: RwLock::new(tags),
+        }
+    }
+
+    fn load_tags(conn: &mut SqliteConnection) -> QueryResult<HashMap<GuildId, HashSet<String>>> {
+        use crate::schema::todo_tags::dsl::{guild_id, tag, todo_tags};
 
-        let todo_list = todos.load::<Todo>(&mut db.get().unwrap()).unwrap();
-        let iterators = todo_list
+        let tags = todo_tags
+            .select((guild_id, tag))
+            .distinct()
+            .load::<(i64, String)>(conn)?
             .into_iter()
-            .group_by(|td| td.channel_id)
+            .into_group_map_by(|(guild, _)| GuildId(*guild as u64))
             .into_iter()
-            .map(|(chnl, tds)| {
-                let biggest_id = tds.map(|t| t.id).max().unwrap_or(0);
-                (ChannelId(chnl as u64), AtomicI32::new(biggest_id + 1))
-            })
-            .collect::<HashMap<_, _>>();
+            .map(|(guild, tgs)| (guild, tgs.into_iter().map(|(_, t)| t).collect()))
+            .collect();
 
-        Self {
-            iterators: Mutex::new(iterators),
+        Ok(tags)
+    }
+
+    /// Replaces cached tags with those in the database, tags of purged TODOs
+    /// disappear with them.
+    pub async fn reload_tags(&self, db: &Conn) -> Result<()> {
+        let conn = &mut db.get()?;
+        let tags = Self::load_tags(conn)?;
+        *self.tags.write().await = tags;
+        Ok(())
+    }
+
+    async fn add_tag(&self, guild_id: GuildId, tag: String) {
+        let mut tags = self.tags.write().await;
+        tags.entry(guild_id).or_default().insert(tag);
+    }
+
+    async fn remove_tag(&self, guild_id: GuildId, tag: &str) {
+        let mut tags = self.tags.write().await;
+        if let Some(tags) = tags.get_mut(&guild_id) {
+            tags.remove(tag);
         }
     }
 
-    fn get_id(&self, channelid: ChannelId) -> i32 {
-        let itr = &mut self.iterators.lock().unwrap();
-        let aint = itr.entry(channelid).or_insert_with(|| AtomicI32::new(0));
-        aint.fetch_add(1, Ordering::SeqCst)
+    async fn get_tags(&self, guild_id: &GuildId) -> HashSet<String> {
+        self.tags
+            .read()
+            .await
+            .get(guild_id)
+            .cloned()
+            .unwrap_or_default()
     }
 }
 
@@ -176,28 +559,76 @@ impl TodoData {
 /// Manage channel TODOs
 #[doc = ""]
 #[doc = "The following commands are supported (`{}` indicate mandatory argument, `[]` indicate optional argument):"]
-#[doc = "- `/todo list [completed] [todo_assignee]` - lists all TODOs in the channel, `completed` flag set to True includes completed TODOs in the list, `todo_assignee` field set to someone will show only TODOs assigned to them"]
-#[doc = "- `/todo add {content} [assignee]` - adds new TODO in the channel, `content` field is required and contains the TODO text, you can assign it to a specific person by using `assignee` field"]
-#[doc = "- `/todo complete {id}` - completes TODO specified by `id`"]
-#[doc = "- `/todo uncomplete {id}` - uncompletes TODO specified by `id`"]
-#[doc = "- `/todo delete {id}` - deletes TODO specified by `id`"]
-#[doc = "- `/todo assign {id} {new_assignee}` - assignees TODO specified by `id` to `new_assignee`"]
-#[doc = "- `/todo move {id} {new_channel}` - moves TODO specified by `id` to `new_channel`"]
-#[doc = "- `/todo edit {id} {new_content}` - replaces content of TODO specified by `id` to {new_content}"]
-#[doc = "- `/todo set_priority {id} {new_priority}` - sets priority of TODO specified by `id` to `new_priority`"]
+#[doc = "- `/todo list [completed] [todo_assignee] [tag] [sort_by_priority] [sort_by_due]` - lists all TODOs in the channel, `completed` flag set to True includes completed TODOs in the list, `todo_assignee` field set to someone will show only TODOs assigned to them or to their roles, `tag` field set to a tag will show only TODOs tagged with it, `sort_by_due` flag sorts TODOs by their due date, TODO selected in the list can be completed, assigned to you, reprioritized and edited with its buttons"]
+#[doc = "- `/todo mine [completed] [sort_by_priority] [sort_by_due]` - lists TODOs assigned to you or to your roles in all channels of the server you can view"]
+#[doc = "- `/todo overview [todo_assignee] [tag] [sort_by_priority] [sort_by_due]` - lists incompleted TODOs in all channels of the server you can view, grouped by channel"]
+#[doc = "- `/todo search {query} [all_channels] [completed]` - lists TODOs matching `query` ordered by relevance, `all_channels` flag searches in all channels of the server"]
+#[doc = "- `/todo add {content} [assignee] [priority] [due] [recurrence] [issue] [thread]` - adds new TODO in the channel, `content` field is required and contains the TODO text, you can assign it to a specific person or role by using `assignee` field, `due` accepts dates like `2024-05-01`, `2024-05-01 14:00`, `tomorrow` or `in 3 days`, `recurrence` is `daily`, `weekly`, `monthly`, `monthly on day {day}` or a cron expression like `0 9 * * mon` and adds the TODO again once it's completed, `issue` flag opens GitHub issue for the TODO in the repository linked to the channel, `thread` starts thread for discussing the TODO, it's archived once the TODO is completed and unarchived once uncompleted"]
+#[doc = "- `/todo complete {ids} [force]` - completes TODOs specified by `ids` and closes their GitHub issues, `ids` are a list of IDs and ranges like `3,5,9-12`, TODOs blocked by uncompleted TODOs are completed only with `force` flag"]
+#[doc = "- `/todo uncomplete {ids}` - uncompletes TODOs specified by `ids` and reopens their GitHub issues"]
+#[doc = "- `/todo delete {ids}` - moves TODOs specified by `ids` to the trash, deleted TODOs are purged after a retention period"]
+#[doc = "- `/todo trash` - lists deleted TODOs in the channel"]
+#[doc = "- `/todo restore {id}` - restores deleted TODO specified by `id`"]
+#[doc = "- `/todo assign {ids} [new_assignees]` - assigns TODOs specified by `ids` to `new_assignees`, members or roles like `@alice @oncall`, the TODOs are unassigned without them"]
+#[doc = "- `/todo watch {ids}` - sends you direct messages about changes of TODOs specified by `ids` made by others"]
+#[doc = "- `/todo unwatch {ids}` - stops sending you direct messages about TODOs specified by `ids`"]
+#[doc = "- `/todo move {ids} {new_channel}` - moves TODOs specified by `ids` to `new_channel`, their GitHub issues are closed"]
+#[doc = "- `/todo copy {ids} {new_channel}` - copies TODOs specified by `ids` with their tags and checklists to `new_channel`"]
+#[doc = "- `/todo move-all {new_channel} [assignee] [priority] [completed]` - moves all TODOs in the channel to `new_channel`, `assignee`, `priority` and `completed` fields limit it to the matching TODOs, their GitHub issues are closed"]
+#[doc = "- `/todo edit {id} [new_content] [due] [recurrence]` - replaces content, due date and/or recurrence of TODO specified by `id`, `due` or `recurrence` set to `none` removes them"]
+#[doc = "- `/todo set_priority {ids} {new_priority}` - sets priority of TODOs specified by `ids` to `new_priority`"]
+#[doc = "- `/todo check add {id} {text}` - adds checklist item to TODO specified by `id`"]
+#[doc = "- `/todo check toggle {id} {item}` - checks or unchecks checklist `item` of TODO specified by `id`, TODO is completed once all items are checked"]
+#[doc = "- `/todo check remove {id} {item}` - removes checklist `item` of TODO specified by `id`"]
+#[doc = "- `/todo tag add {id} {tag}` - tags TODO specified by `id` with `tag`"]
+#[doc = "- `/todo tag remove {id} {tag}` - removes `tag` from TODO specified by `id`"]
+#[doc = "- `/todo blocker add {id} {blocker_id} [blocker_channel]` - marks TODO specified by `id` as blocked by TODO `blocker_id` from `blocker_channel` of the server, this channel by default, the assigned members get a direct message once all its blockers are completed"]
+#[doc = "- `/todo blocker remove {id} {blocker_id} [blocker_channel]` - removes blocker `blocker_id` from TODO specified by `id`"]
+#[doc = "- `/todo github link {repository}` - links GitHub `repository` allowed by the bot owner to the channel, its opened and closed issues are periodically mirrored to the TODOs"]
+#[doc = "- `/todo github unlink` - unlinks GitHub repository from the channel"]
+#[doc = "- `/todo pin-board` - posts and pins board with incompleted TODOs in the channel, the board is updated whenever they change"]
+#[doc = "- `/todo threads {enabled}` - sets whether TODOs added in the channel get their own threads unless `thread` is given to `/todo add`"]
+#[doc = "- `/todo reminders [enabled] [interval] [time] [quiet_days] [reset]` - configures periodic reminders in the channel, `interval` is the number of days between them, `time` is time of day in UTC, `quiet_days` are days without reminders, e.g. `sat,sun` or `none`, `reset` flag reverts to the defaults"]
+#[doc = "- `/todo export [format]` - exports TODOs in the channel to a JSON, CSV or Markdown file"]
+#[doc = "- `/todo import {file} [format]` - adds TODOs from a JSON, CSV or Markdown file to the channel, rows with invalid values are skipped and reported"]
+#[doc = "- `/todo stats [period] [all_channels]` - shows how many TODOs were created and completed in the last week, month, quarter or year, how long completing them took on average, who completed them, open TODOs by priority and a burndown chart, `all_channels` flag includes all channels of the server"]
+#[doc = "- `/todo history {id}` - shows who changed TODO specified by `id` and how, including deleted TODOs"]
+#[doc = "- `/todo remind {id} {when}` - sends you (or the assigned members, if there are any) a direct message about TODO specified by `id` at `when`, e.g. `in 2 hours` or `2024-05-01 14:00`"]
+#[doc = "- `Create TODO` in the message context menu (Apps) - opens a modal prefilled with the message content and adds TODO linking to the message, the link is shown in the list"]
 #[allow(clippy::unused_async)]
 #[poise::command(
     slash_command,
     subcommands(
         "list",
+        "mine",
+        "overview",
+        "search",
         "add",
         "complete",
         "uncomplete",
         "delete",
+        "trash",
+        "restore",
         "assign",
+        "watch",
+        "unwatch",
         "rmove",
+        "copy",
+        "move_all",
         "edit",
-        "set_priority"
+        "set_priority",
+        "history",
+        "export",
+        "import",
+        "remind",
+        "reminders",
+        "check",
+        "tag",
+        "blocker",
+        "github",
+        "pin_board",
+        "threads",
+        "stats"
     )
 )]
 pub async fn todo(_ctx: Context<'_>) -> Result<()> {
@@ -207,10 +638,21 @@ pub async fn todo(_ctx: Context<'_>) -> Result<()> {
 // TODO: division of responsibilites, extract database manipulations to other
 // functions
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Scope {
+    Channel,
+    Guild,
+}
+
 struct QueryData {
+    scope: Scope,
+    deleted: bool,
     completed: bool,
     todo_assignee: Option<Member>,
+    tag: Option<String>,
+    search: Option<String>,
     sort_by_priority: bool,
+    sort_by_due: bool,
 }
 
 /// List TODO entries
@@ -221,60 +663,378 @@ pub async fn list(
     #[flag]
     completed: bool,
     #[description = "Show only TODOs assigned to"] todo_assignee: Option<Member>,
+    #[description = "Show only TODOs with tag"]
+    #[autocomplete = "autocomplete_tag"]
+    tag: Option<String>,
+    #[description = "Sort TODOs by priority"]
+    #[flag]
+    sort_by_priority: bool,
+    #[description = "Sort TODOs by due date"]
+    #[flag]
+    sort_by_due: bool,
+) -> Result<()> {
+    let query_data = QueryData {
+        scope: Scope::Channel,
+        deleted: false,
+        completed,
+        todo_assignee,
+        tag: tag.as_deref().map(normalize_tag),
+        search: None,
+        sort_by_priority,
+        sort_by_due,
+    };
+    let data = get_todos(ctx, &query_data).await;
+
+    match data {
+        EmbedData::Text(text) => respond_text(ctx, text, false).await,
+        EmbedData::Fields(fields) => respond_fields(ctx, fields, query_data).await,
+    };
+
+    Ok(())
+}
+
+/// List TODO entries assigned to you in all channels
+#[poise::command(slash_command, guild_only)]
+pub async fn mine(
+    ctx: Context<'_>,
+    #[description = "Show completed TODOs"]
+    #[flag]
+    completed: bool,
     #[description = "Sort TODOs by priority"]
     #[flag]
     sort_by_priority: bool,
+    #[description = "Sort TODOs by due date"]
+    #[flag]
+    sort_by_due: bool,
 ) -> Result<()> {
+    let Some(member) = ctx.author_member().await else {
+        respond_text(ctx, "Listing TODOs failed.".to_string(), true).await;
+        return Ok(());
+    };
+
     let query_data = QueryData {
+        scope: Scope::Guild,
+        deleted: false,
         completed,
+        todo_assignee: Some(member.into_owned()),
+        tag: None,
+        search: None,
+        sort_by_priority,
+        sort_by_due,
+    };
+    let data = get_todos(ctx, &query_data).await;
+
+    match data {
+        EmbedData::Text(text) => respond_text(ctx, text, false).await,
+        EmbedData::Fields(fields) => respond_fields(ctx, fields, query_data).await,
+    }
+
+    Ok(())
+}
+
+/// List open TODO entries in all channels
+#[poise::command(slash_command, guild_only)]
+pub async fn overview(
+    ctx: Context<'_>,
+    #[description = "Show only TODOs assigned to"] todo_assignee: Option<Member>,
+    #[description = "Show only TODOs with tag"]
+    #[autocomplete = "autocomplete_tag"]
+    tag: Option<String>,
+    #[description = "Sort TODOs by priority"]
+    #[flag]
+    sort_by_priority: bool,
+    #[description = "Sort TODOs by due date"]
+    #[flag]
+    sort_by_due: bool,
+) -> Result<()> {
+    let query_data = QueryData {
+        scope: Scope::Guild,
+        deleted: false,
+        completed: false,
         todo_assignee,
+        tag: tag.as_deref().map(normalize_tag),
+        search: None,
         sort_by_priority,
+        sort_by_due,
     };
     let data = get_todos(ctx, &query_data).await;
 
     match data {
         EmbedData::Text(text) => respond_text(ctx, text, false).await,
         EmbedData::Fields(fields) => respond_fields(ctx, fields, query_data).await,
+    }
+
+    Ok(())
+}
+
+/// Search TODO entries
+#[poise::command(slash_command)]
+pub async fn search(
+    ctx: Context<'_>,
+    #[description = "Search query"]
+    #[max_length = 100]
+    query: String,
+    #[description = "Search in all channels of the server"]
+    #[flag]
+    all_channels: bool,
+    #[description = "Include completed TODOs"]
+    #[flag]
+    completed: bool,
+) -> Result<()> {
+    if query.trim().is_empty() {
+        respond_text(ctx, "Search query can't be empty.".to_string(), true).await;
+        return Ok(());
+    }
+
+    let query_data = QueryData {
+        scope: if all_channels {
+            Scope::Guild
+        } else {
+            Scope::Channel
+        },
+        deleted: false,
+        completed,
+        todo_assignee: None,
+        tag: None,
+        search: Some(query.trim().to_string()),
+        sort_by_priority: false,
+        sort_by_due: false,
     };
+    let data = get_todos(ctx, &query_data).await;
+
+    match data {
+        EmbedData::Text(text) => respond_text(ctx, text, false).await,
+        EmbedData::Fields(fields) => respond_fields(ctx, fields, query_data).await,
+    }
 
     Ok(())
 }
 
+/// Turns user input into FTS5 query matching all of its words, every word is
+/// quoted so that no input is interpreted as the query syntax.
+fn get_match_expression(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .join(" ")
+}
+
+/// Returns keys of TODOs in the channels matching the FTS5 expression,
+/// ordered by relevance.
+fn search_todos(
+    conn: &mut SqliteConnection,
+    expression: &str,
+    channels: &[i64],
+) -> QueryResult<Vec<(i64, i32)>> {
+    if channels.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let placeholders = vec!["?"; channels.len()].join(", ");
+    let mut query = diesel::sql_query(format!(
+        "SELECT channel_id, todo_id FROM todos_fts WHERE todos_fts MATCH ? AND channel_id IN \
+         ({placeholders}) ORDER BY rank"
+    ))
+    .into_boxed()
+    .bind::<Text, _>(expression);
+    for channel in channels {
+        query = query.bind::<BigInt, _>(*channel);
+    }
+
+    Ok(query
+        .load::<SearchMatch>(conn)?
+        .into_iter()
+        .map(|m| (m.channel_id, m.todo_id))
+        .collect())
+}
+
+/// Returns IDs of channels the query covers, in the order they should be
+/// listed in. Server-wide queries cover only channels the invoker can view.
+async fn get_channels(ctx: Context<'_>, scope: Scope) -> Option<Vec<i64>> {
+    match scope {
+        Scope::Channel => Some(vec![i64::from(ctx.channel_id())]),
+        Scope::Guild => {
+            let member = ctx.author_member().await?;
+            get_visible_channels(ctx, &member).await
+        }
+    }
+}
+
+/// Returns IDs of channels and active threads of the guild which the member
+/// can view, threads follow the channels.
+async fn get_visible_channels(ctx: Context<'_>, member: &Member) -> Option<Vec<i64>> {
+    let guild = ctx.guild()?;
+    let channels = guild.id.channels(ctx).await.ok()?;
+    // Threads aren't part of the channel list
+    let threads = guild
+        .id
+        .get_active_threads(ctx)
+        .await
+        .map(|t| t.threads)
+        .unwrap_or_default();
+
+    let visible = channels
+        .values()
+        .sorted_by_key(|c| (c.position, c.id))
+        .chain(&threads)
+        .filter(|c| can_view(&guild, member, c, &channels))
+        .map(|c| i64::from(c.id))
+        .collect();
+    Some(visible)
+}
+
+/// Returns whether the member can view the channel. Threads are visible with
+/// their parent channel, private ones only to members managing threads as
+/// thread membership isn't known.
+fn can_view(
+    guild: &Guild,
+    member: &Member,
+    channel: &GuildChannel,
+    channels: &HashMap<ChannelId, GuildChannel>,
+) -> bool {
+    let (target, required) = match channel.kind {
+        ChannelType::PublicThread | ChannelType::NewsThread => (
+            channel.parent_id.and_then(|parent| channels.get(&parent)),
+            Permissions::VIEW_CHANNEL,
+        ),
+        ChannelType::PrivateThread => (
+            channel.parent_id.and_then(|parent| channels.get(&parent)),
+            Permissions::VIEW_CHANNEL | Permissions::MANAGE_THREADS,
+        ),
+        _ => (Some(channel), Permissions::VIEW_CHANNEL),
+    };
+
+    target
+        .and_then(|target| guild.user_permissions_in(target, member).ok())
+        .is_some_and(|permissions| permissions.contains(required))
+}
+
 async fn get_todos(ctx: Context<'_>, query_data: &QueryData) -> EmbedData {
-    use crate::schema::todos::dsl::{assignee, channel_id, completion_date, todos};
+    use tokio_stream::StreamExt;
 
-    let mut query = todos
-        .into_boxed()
-        .filter(channel_id.eq(i64::from(ctx.channel_id())));
+    use crate::schema::{
+        todo_assignees, todo_tags,
+        todos::dsl::{channel_id, completion_date, deletion_date, id, todos},
+    };
+
+    let Some(channels) = get_channels(ctx, query_data.scope).await else {
+        return EmbedData::Text("Listing TODOs failed.".to_string());
+    };
+
+    let mut query = todos.into_boxed().filter(channel_id.eq_any(&channels));
+
+    query = if query_data.deleted {
+        query.filter(deletion_date.is_not_null())
+    } else {
+        query.filter(deletion_date.is_null())
+    };
+
+    let ranking = match &query_data.search {
+        Some(search) => {
+            let expression = get_match_expression(search);
+            let conn = &mut ctx.data().db.get().unwrap();
+            let Ok(matches) = search_todos(conn, &expression, &channels) else {
+                return EmbedData::Text("Searching TODOs failed.".to_string());
+            };
+            // Exact keys are checked once the TODOs are loaded
+            let ids: HashSet<i32> = matches.iter().map(|(_, todo)| *todo).collect();
+            query = query.filter(id.eq_any(ids));
+            Some(matches)
+        }
+        None => None,
+    };
 
     if !query_data.completed {
         query = query.filter(completion_date.is_null());
     };
 
     if let Some(member) = &query_data.todo_assignee {
-        query = query.filter(assignee.eq(member.user.id.0 as i64));
+        query = query.filter(diesel::dsl::exists(
+            todo_assignees::table
+                .filter(todo_assignees::channel_id.eq(channel_id))
+                .filter(todo_assignees::todo_id.eq(id))
+                .filter(is_assigned_to(member)),
+        ));
     };
 
-    let results = query.load::<Todo>(&mut ctx.data().db.get().unwrap());
+    if let Some(tag) = &query_data.tag {
+        query = query.filter(diesel::dsl::exists(
+            todo_tags::table
+                .filter(todo_tags::channel_id.eq(channel_id))
+                .filter(todo_tags::todo_id.eq(id))
+                .filter(todo_tags::tag.eq(tag)),
+        ));
+    }
+
+    let (results, assignees, progress, tags, blockers) = {
+        let conn = &mut ctx.data().db.get().unwrap();
+        (
+            query.load::<Todo>(conn),
+            get_assignees(conn, &channels),
+            get_checklist_progress(conn, &channels),
+            get_tags(conn, &channels),
+            dependency::get_open_blockers(conn, &channels),
+        )
+    };
 
+    let results = results
+        .map(|todo_list| match &ranking {
+            Some(ranking) => todo_list
+                .into_iter()
+                .filter(|todo| ranking.contains(&(todo.channel_id, todo.id)))
+                .collect(),
+            None => todo_list,
+        })
+        .and_then(|todo_list| Ok((todo_list, assignees?, progress?, tags?, blockers?)));
     match results {
-        Ok(todo_list) => {
+        Ok((todo_list, assignees, progress, mut tags, mut blockers)) => {
             let mut output: Vec<TodoEntry> = vec![];
             let mut todos_stream = stream::iter(todo_list);
 
             while let Some(t) = todos_stream.next().await {
-                let completed = t.completion_date.is_some();
-                let entry =
-                    TodoEntry::new(t.id, t.assignee, t.todo, completed, t.priority, ctx).await;
+                let key = (t.channel_id, t.id);
+                let todo_assignees = assignees.get(&key).map_or(&[][..], Vec::as_slice);
+                let mut entry = TodoEntry::new(t, todo_assignees, ctx).await;
+                entry.checklist = progress.get(&key).copied();
+                entry.tags = tags.remove(&key).unwrap_or_default();
+                entry.blocked_by = blockers.remove(&key).unwrap_or_default();
                 output.push(entry);
             }
 
+            if let Some(ranking) = &ranking {
+                output.sort_by_key(|entry| {
+                    ranking
+                        .iter()
+                        .position(|key| *key == (i64::from(entry.channel_id), entry.id))
+                });
+            }
+
+            if query_data.sort_by_due {
+                output.sort_by_key(|entry| (entry.due_date.is_none(), entry.due_date));
+            }
+
             if query_data.sort_by_priority {
                 output.sort_by_key(|entry| -entry.priority);
             }
 
-            if output.is_empty() {
-                EmbedData::Text("There are no incompleted TODOs in this channel.".to_string())
+            if query_data.scope == Scope::Guild && ranking.is_none() {
+                output.sort_by_key(|entry| {
+                    channels
+                        .iter()
+                        .position(|c| *c == i64::from(entry.channel_id))
+                });
+            }
+
+            if output.is_empty() && query_data.deleted {
+                EmbedData::Text("There are no deleted TODOs in this channel.".to_string())
+            } else if output.is_empty() && ranking.is_some() {
+                EmbedData::Text("No TODOs match the query.".to_string())
+            } else if output.is_empty() {
+                let place = match query_data.scope {
+                    Scope::Channel => "channel",
+                    Scope::Guild => "server",
+                };
+                EmbedData::Text(format!("There are no incompleted TODOs in this {place}."))
             } else {
                 EmbedData::Fields(output)
             }
@@ -284,106 +1044,2009 @@ async fn get_todos(ctx: Context<'_>, query_data: &QueryData) -> EmbedData {
     }
 }
 
+fn get_checklist_progress(
+    conn: &mut SqliteConnection,
+    channels: &[i64],
+) -> QueryResult<HashMap<(i64, i32), ChecklistProgress>> {
+    use crate::schema::todo_checklist_items::dsl::{
+        channel_id, checked, todo_checklist_items, todo_id,
+    };
+
+    let items = todo_checklist_items
+        .filter(channel_id.eq_any(channels))
+        .select((channel_id, todo_id, checked))
+        .load::<(i64, i32, bool)>(conn)?;
+
+    let mut progress: HashMap<(i64, i32), ChecklistProgress> = HashMap::new();
+    for (channel, todo, is_checked) in items {
+        let entry = progress.entry((channel, todo)).or_default();
+        entry.total += 1;
+        if is_checked {
+            entry.checked += 1;
+        }
+    }
+
+    Ok(progress)
+}
+
+fn get_assignees(
+    conn: &mut SqliteConnection,
+    channels: &[i64],
+) -> QueryResult<HashMap<(i64, i32), Vec<Assignee>>> {
+    use crate::schema::todo_assignees::dsl::{
+        assignee_id, channel_id, is_role, todo_assignees, todo_id,
+    };
+
+    let assignees = todo_assignees
+        .filter(channel_id.eq_any(channels))
+        .select((channel_id, todo_id, assignee_id, is_role))
+        .order((is_role.asc(), assignee_id.asc()))
+        .load::<(i64, i32, i64, bool)>(conn)?
+        .into_iter()
+        .into_group_map_by(|(channel, todo, _, _)| (*channel, *todo))
+        .into_iter()
+        .map(|(key, assignees)| {
+            let assignees = assignees
+                .into_iter()
+                .map(|(_, _, id, role)| Assignee::from_row(id, role))
+                .collect();
+            (key, assignees)
+        })
+        .collect();
+
+    Ok(assignees)
+}
+
+/// Matches assignees the member is, either directly or by one of their roles.
+#[diesel::dsl::auto_type(no_type_alias)]
+fn is_assigned_to(member: &Member) -> _ {
+    let user: i64 = member.user.id.0 as i64;
+    let roles: Vec<i64> = member.roles.iter().map(|role| role.0 as i64).collect();
+    // Paths are spelled out, the return type is derived from them
+    crate::schema::todo_assignees::is_role
+        .eq(false)
+        .and(crate::schema::todo_assignees::assignee_id.eq(user))
+        .or(crate::schema::todo_assignees::is_role
+            .eq(true)
+            .and(crate::schema::todo_assignees::assignee_id.eq_any(roles)))
+}
+
+/// Returns nickname of the member or name of the role, `None` if it's no
+/// longer in the guild.
+async fn get_assignee_name(
+    cache_http: &impl CacheHttp,
+    guild: GuildId,
+    assignee: Assignee,
+) -> Option<String> {
+    match assignee {
+        Assignee::Member(user) => guild
+            .member(cache_http, user)
+            .await
+            .ok()
+            .map(|member| utils::get_nick_from_member(&member)),
+        Assignee::Role(role) => {
+            let cached = cache_http.cache().and_then(|cache| cache.role(guild, role));
+            let role = match cached {
+                Some(role) => Some(role),
+                None => guild
+                    .roles(cache_http.http())
+                    .await
+                    .ok()
+                    .and_then(|mut roles| roles.remove(&role)),
+            };
+            role.map(|role| format!("@{}", role.name))
+        }
+    }
+}
+
+fn get_tags(
+    conn: &mut SqliteConnection,
+    channels: &[i64],
+) -> QueryResult<HashMap<(i64, i32), Vec<String>>> {
+    use crate::schema::todo_tags::dsl::{channel_id, tag, todo_id, todo_tags};
+
+    let tags = todo_tags
+        .filter(channel_id.eq_any(channels))
+        .select((channel_id, todo_id, tag))
+        .order(tag.asc())
+        .load::<(i64, i32, String)>(conn)?
+        .into_iter()
+        .into_group_map_by(|(channel, todo, _)| (*channel, *todo))
+        .into_iter()
+        .map(|(key, tags)| (key, tags.into_iter().map(|(_, _, t)| t).collect()))
+        .collect();
+
+    Ok(tags)
+}
+
 /// Add TODO entry
+#[allow(clippy::too_many_arguments)]
 #[poise::command(slash_command)]
 pub async fn add(
     ctx: Context<'_>,
     #[description = "TODO content"] content: String,
-    #[description = "TODO assignee"] assignee: Option<Member>,
+    #[description = "TODO assignee, member or role"] assignee: Option<Assignee>,
     #[description = "TODO priority"] priority: Option<Priority>,
+    #[description = "TODO due date, e.g. `in 3 days`"] due: Option<String>,
+    #[description = "Repeat TODO once completed, e.g. `weekly` or `0 9 * * mon`"]
+    recurrence: Option<String>,
+    #[description = "Open GitHub issue in the repository linked to the channel"]
+    #[flag]
+    issue: bool,
+    #[description = "Create thread for the TODO, channel default if unset"] thread: Option<bool>,
 ) -> Result<()> {
-    use crate::{schema::todos::dsl::todos, utils};
+    use crate::schema::todos::dsl::todos;
+
+    let due_date = due.as_deref().map(parse_due_date);
+    let recurrence = recurrence.as_deref().map(str::parse::<Recurrence>);
+    // Set to the new TODO if it gets its own thread
+    let mut thread_todo = None;
 
     let data = if content.len() > 1024 {
         "Content can't have more than 1024 characters.".to_string()
+    } else if let Some(None) = due_date {
+        "Invalid due date.".to_string()
+    } else if let Some(Err(e)) = recurrence {
+        format!("Invalid recurrence, {e}.")
     } else {
+        let issue = if issue {
+            ctx.defer().await?;
+            match github::open_issue(ctx, &content).await {
+                Ok(Some(issue)) => Some(issue),
+                Ok(None) => {
+                    let data = "Channel isn't linked to any repository.".to_string();
+                    respond_text(ctx, data, true).await;
+                    return Ok(());
+                }
+                Err(e) => {
+                    debug!("{:?}", e);
+                    respond_text(ctx, "Opening issue failed.".to_string(), true).await;
+                    return Ok(());
+                }
+            }
+        } else {
+            None
+        };
+
         let time = OffsetDateTime::now_utc().format(&TIME_FORMAT).unwrap();
-        let new_id = ctx.data().todo_data.get_id(ctx.channel_id());
-        let nickname = match &assignee {
-            Some(m) => utils::get_nick_from_member(m),
-            None => "no one".to_string(),
+        let channel = i64::from(ctx.channel_id());
+        let nickname = match (assignee, ctx.guild_id()) {
+            (Some(assignee), Some(guild)) => get_assignee_name(&ctx, guild, assignee)
+                .await
+                .unwrap_or_else(|| assignee.mention().to_string()),
+            (Some(assignee), None) => assignee.mention().to_string(),
+            (None, _) => "no one".to_string(),
         };
-        let assignee = assignee.map(|m| m.user.id.0 as i64);
+        let assignees: Vec<Assignee> = assignee.into_iter().collect();
 
         let priority = priority.unwrap_or_default() as i32;
 
-        let new_todo = NewTodo {
-            channel_id: &(i64::from(ctx.channel_id())),
-            id: &new_id,
-            todo: &content,
-            creation_date: &time,
-            assignee,
-            priority,
-        };
+        let due_date = due_date.flatten();
+        let recurrence = recurrence
+            .and_then(std::result::Result::ok)
+            .map(|r| r.to_string());
+
+        let result = ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+            let new_id = next_todo_id(conn, channel)?;
+            let new_todo = NewTodo {
+                channel_id: &channel,
+                id: &new_id,
+                todo: &content,
+                creation_date: &time,
+                completion_date: None,
+                priority,
+                due_date,
+                recurrence,
+                issue,
+                message_link: None,
+            };
+            diesel::insert_into(todos).values(&new_todo).execute(conn)?;
+            set_assignees(conn, channel, new_id, &assignees)?;
+            log_event(
+                conn,
+                ctx,
+                channel,
+                new_id,
+                TodoAction::Created,
+                None,
+                Some(&content),
+            )?;
+            let thread = match thread {
+                Some(thread) => thread,
+                None => thread::creates_threads(conn, channel)?,
+            };
+            Ok((new_id, thread))
+        });
 
-        let result = diesel::insert_into(todos)
-            .values(&new_todo)
-            .execute(&mut ctx.data().db.get().unwrap());
+        if let (Err(_), Some(issue)) = (&result, issue) {
+            github::close_issue(ctx.data(), channel, issue).await;
+        }
 
         match result {
-            Ok(_) => MessageBuilder::new()
-                .push(format!("TODO [{}] (", &new_id))
-                .push_mono_safe(&content)
-                .push(format!(") added and assigned to {nickname}."))
-                .build(),
+            Ok((new_id, thread)) => {
+                thread_todo = thread.then_some(new_id);
+                MessageBuilder::new()
+                    .push(format!("TODO [{new_id}] ("))
+                    .push_mono_safe(&content)
+                    .push(format!(") added and assigned to {nickname}."))
+                    .push(issue.map_or(String::new(), |issue| format!(" Opened issue #{issue}.")))
+                    .build()
+            }
             Err(NotFound) => "Not found.".to_string(),
+            Err(DatabaseError(UniqueViolation, _)) => ID_CONFLICT.to_string(),
             Err(_) => "Adding TODO failed.".to_string(),
         }
     };
 
-    respond_text(ctx, data, false).await;
+    let Some(new_id) = thread_todo else {
+        respond_text(ctx, data, false).await;
+        return Ok(());
+    };
+
+    // The thread is started on the response, so it has to be sent first
+    let reply = ctx
+        .send(|reply| reply.embed(|embed| embed.description(data)))
+        .await?;
+    let message = reply.message().await?;
+    if let Err(e) = thread::create_thread(ctx, &message, new_id, &content).await {
+        debug!("Creating TODO thread failed: {:?}", e);
+        respond_text(ctx, "Creating thread failed.".to_string(), true).await;
+    }
 
     Ok(())
 }
 
-/// Delete TODO entry
+/// Delete TODO entries
 #[poise::command(slash_command)]
-pub async fn delete(ctx: Context<'_>, #[description = "TODO id"] todo_id: i64) -> Result<()> {
-    use crate::schema::todos::dsl::{channel_id, id, todo, todos};
+pub async fn delete(
+    ctx: Context<'_>,
+    #[description = "TODO ids, e.g. `3,5,9-12`"] todo_ids: IdSelection,
+) -> Result<()> {
+    use crate::schema::todos::dsl::{channel_id, deletion_date, id, todo, todos};
 
-    let deleted: QueryResult<String> = diesel::delete(todos)
-        .filter(channel_id.eq(i64::from(ctx.channel_id())))
-        .filter(id.eq(todo_id as i32))
-        .returning(todo)
-        .get_result(&mut ctx.data().db.get().unwrap());
+    let channel = i64::from(ctx.channel_id());
+    let time = OffsetDateTime::now_utc().format(&TIME_FORMAT).unwrap();
+
+    let deleted = ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+        change_todos(conn, todo_ids.ids(), |conn, todo_id| {
+            let deleted = diesel::update(todos)
+                .filter(channel_id.eq(channel))
+                .filter(id.eq(todo_id))
+                .filter(deletion_date.is_null())
+                .set(deletion_date.eq(&time))
+                .returning(todo)
+                .get_result::<String>(conn)?;
+            log_event(
+                conn,
+                ctx,
+                channel,
+                todo_id,
+                TodoAction::Deleted,
+                Some(&deleted),
+                None,
+            )?;
+            Ok(deleted)
+        })
+    });
 
-    let data = match deleted {
-        Ok(deleted) => MessageBuilder::new()
-            .push(format!("TODO [{}] (", &todo_id))
-            .push_mono_safe(&deleted)
+    let restorable = changed_ids(&deleted);
+    let data = format_changed(deleted, "Deleting TODO failed.", |changed| match changed {
+        [(todo_id, content)] => MessageBuilder::new()
+            .push(format!("TODO [{todo_id}] ("))
+            .push_mono_safe(content)
             .push(") deleted.")
             .build(),
+        _ => format!("TODOs {} deleted.", format_ids(&restorable)),
+    });
+
+    if restorable.is_empty() {
+        respond_text(ctx, data, true).await;
+    } else {
+        let closed: Vec<(i64, i32)> = restorable
+            .iter()
+            .map(|todo_id| (channel, *todo_id))
+            .collect();
+        dependency::notify_unblocked(ctx.data(), ctx.http(), &closed, &[]).await;
+        respond_undo(ctx, restorable, data).await;
+    }
+
+    Ok(())
+}
+
+/// Sends response with a button restoring the deleted TODOs.
+async fn respond_undo(ctx: Context<'_>, todo_ids: Vec<i32>, text: String) {
+    let ctx_id = ctx.id();
+    let undo_button_id = format!("{ctx_id}undo");
+
+    let response = ctx
+        .send(|reply| {
+            reply
+                .embed(|embed| embed.description(&text))
+                .components(|comp| {
+                    comp.create_action_row(|ar| {
+                        ar.create_button(|cb| {
+                            cb.custom_id(&undo_button_id)
+                                .label("Undo")
+                                .style(ButtonStyle::Secondary)
+                        })
+                    })
+                })
+                .ephemeral(true)
+        })
+        .await;
+
+    let reply_handle = match response {
+        Ok(reply_handle) => reply_handle,
+        Err(e) => {
+            debug!("{:?}", e);
+            return;
+        }
+    };
+
+    let message_id = match reply_handle.message().await {
+        Ok(message) => message.id,
+        Err(e) => {
+            debug!("{:?}", e);
+            return;
+        }
+    };
+
+    let button = poise::serenity_prelude::CollectComponentInteraction::new(ctx.serenity_context())
+        .timeout(UNDO_TIMEOUT)
+        .message_id(message_id)
+        .filter(move |comp| comp.data.custom_id == undo_button_id)
+        .await;
+
+    let Some(button) = button else {
+        let response = reply_handle
+            .edit(ctx, |reply| {
+                reply
+                    .embed(|embed| embed.description(text))
+                    .components(|comp| comp)
+            })
+            .await;
+
+        if let Err(e) = response {
+            debug!("{:?}", e);
+        }
+        return;
+    };
+
+    let restored = ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+        change_todos(conn, &todo_ids, |conn, todo_id| {
+            restore_todo(conn, ctx, todo_id)
+        })
+    });
+    let data = format_changed(
+        restored,
+        "Restoring TODO failed.",
+        |changed| match changed {
+            [(todo_id, content)] => MessageBuilder::new()
+                .push(format!("TODO [{todo_id}] ("))
+                .push_mono_safe(content)
+                .push(") restored.")
+                .build(),
+            _ => format!("TODOs {} restored.", format_ids(&todo_ids)),
+        },
+    );
+
+    let response = button
+        .create_interaction_response(ctx, |ir| {
+            ir.kind(poise::serenity_prelude::InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|ird| {
+                    ird.embed(|ce| ce.description(data)).components(|comp| comp)
+                })
+        })
+        .await;
+
+    if let Err(e) = response {
+        debug!("{:?}", e);
+    }
+}
+
+/// Restore deleted TODO entry
+#[poise::command(slash_command)]
+pub async fn restore(ctx: Context<'_>, #[description = "TODO id"] todo_id: i64) -> Result<()> {
+    let restored = ctx
+        .data()
+        .db
+        .get()
+        .unwrap()
+        .immediate_transaction(|conn| restore_todo(conn, ctx, todo_id as i32));
+    let data = match restored {
+        Ok(restored) => MessageBuilder::new()
+            .push(format!("TODO [{todo_id}] ("))
+            .push_mono_safe(&restored)
+            .push(") restored.")
+            .build(),
         Err(NotFound) => "Not found.".to_string(),
-        Err(_) => "Deleting TODO failed.".to_string(),
+        Err(_) => "Restoring TODO failed.".to_string(),
+    };
+
+    respond_text(ctx, data, true).await;
+
+    Ok(())
+}
+
+/// Restores the deleted TODO, has to be called in a transaction.
+fn restore_todo(
+    conn: &mut SqliteConnection,
+    ctx: Context<'_>,
+    todo_id: i32,
+) -> QueryResult<String> {
+    use crate::schema::todos::dsl::{channel_id, deletion_date, id, todo, todos};
+
+    let channel = i64::from(ctx.channel_id());
+
+    let restored = diesel::update(todos)
+        .filter(channel_id.eq(channel))
+        .filter(id.eq(todo_id))
+        .filter(deletion_date.is_not_null())
+        .set(deletion_date.eq::<Option<String>>(None))
+        .returning(todo)
+        .get_result::<String>(conn)?;
+    log_event(
+        conn,
+        ctx,
+        channel,
+        todo_id,
+        TodoAction::Restored,
+        None,
+        None,
+    )?;
+    Ok(restored)
+}
+
+/// List deleted TODO entries
+#[poise::command(slash_command)]
+pub async fn trash(ctx: Context<'_>) -> Result<()> {
+    let query_data = QueryData {
+        scope: Scope::Channel,
+        deleted: true,
+        completed: true,
+        todo_assignee: None,
+        tag: None,
+        search: None,
+        sort_by_priority: false,
+        sort_by_due: false,
+    };
+    let data = get_todos(ctx, &query_data).await;
+
+    match data {
+        EmbedData::Text(text) => respond_text(ctx, text, true).await,
+        EmbedData::Fields(fields) => respond_fields(ctx, fields, query_data).await,
+    }
+
+    Ok(())
+}
+
+/// Complete TODO entries
+#[poise::command(slash_command)]
+pub async fn complete(
+    ctx: Context<'_>,
+    #[description = "TODO ids, e.g. `3,5,9-12`"] todo_ids: IdSelection,
+    #[description = "Complete TODOs even if they are blocked by uncompleted TODOs"]
+    #[flag]
+    force: bool,
+) -> Result<()> {
+    let channel = i64::from(ctx.channel_id());
+    let actor = ctx.author().id;
+
+    let result = ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+        let blocked = if force {
+            vec![]
+        } else {
+            dependency::get_blocked(conn, channel, todo_ids.ids())?
+        };
+        let unblocked: Vec<i32> = todo_ids
+            .ids()
+            .iter()
+            .filter(|todo_id| !blocked.contains(todo_id))
+            .copied()
+            .collect();
+        let completed = change_todos(conn, &unblocked, |conn, todo_id| {
+            complete_todo(conn, actor, channel, todo_id)
+        })?;
+        Ok((completed, blocked))
+    });
+    let (completed, blocked) = match result {
+        Ok((completed, blocked)) => (Ok(completed), blocked),
+        Err(e) => (Err(e), vec![]),
+    };
+
+    let ids = changed_ids(&completed);
+    let closed: Vec<(i64, i32)> = ids.iter().map(|todo_id| (channel, *todo_id)).collect();
+    after_completed(ctx.data(), ctx.http(), &closed, true).await;
+
+    let blocked = match &blocked[..] {
+        [] => None,
+        [todo_id] => Some(format!(
+            "TODO [{todo_id}] wasn't completed, it's blocked by uncompleted TODOs. Set `force` \
+             to complete it anyway."
+        )),
+        _ => Some(format!(
+            "TODOs {} weren't completed, they are blocked by uncompleted TODOs. Set `force` to \
+             complete them anyway.",
+            format_ids(&blocked)
+        )),
+    };
+
+    let data = match (completed, blocked) {
+        (Ok((changed, missing)), Some(blocked)) if changed.is_empty() && missing.is_empty() => {
+            blocked
+        }
+        (completed, blocked) => {
+            let data = format_changed(completed, "Completing TODO failed.", |completed| {
+                format_completed(completed, &ids)
+            });
+            match blocked {
+                Some(blocked) => format!("{data} {blocked}"),
+                None => data,
+            }
+        }
+    };
+
+    respond_text(ctx, data, false).await;
+
+    Ok(())
+}
+
+fn format_completed(completed: &[(i32, (Todo, Option<i32>))], ids: &[i32]) -> String {
+    let mut msg = MessageBuilder::new();
+    if let [(todo_id, (completed, _))] = completed {
+        msg.push(format!("TODO [{todo_id}] ("))
+            .push_mono_safe(&completed.todo)
+            .push(") completed.");
+    } else {
+        msg.push(format!("TODOs {} completed.", format_ids(ids)));
+    }
+    let next: Vec<i32> = completed
+        .iter()
+        .filter_map(|(_, (_, next))| *next)
+        .collect();
+    match &next[..] {
+        [] => {}
+        [next] => {
+            msg.push(format!(" Next occurrence added as TODO [{next}]."));
+        }
+        _ => {
+            msg.push(format!(
+                " Next occurrences added as TODOs {}.",
+                format_ids(&next)
+            ));
+        }
+    }
+    msg.build()
+}
+
+/// Completes the TODO, adding its next occurrence if it's recurring and it
+/// wasn't completed already. Has to be called in a transaction.
+fn complete_todo(
+    conn: &mut SqliteConnection,
+    actor: UserId,
+    channel: i64,
+    todo_id: i32,
+) -> QueryResult<(Todo, Option<i32>)> {
+    use crate::schema::todos::dsl::{channel_id, completion_date, deletion_date, id, todos};
+
+    let time = OffsetDateTime::now_utc().format(&TIME_FORMAT).unwrap();
+
+    let was_completed = todos
+        .filter(channel_id.eq(channel))
+        .filter(id.eq(todo_id))
+        .filter(deletion_date.is_null())
+        .select(completion_date.is_not_null())
+        .first::<bool>(conn)?;
+    let completed = diesel::update(todos)
+        .filter(channel_id.eq(channel))
+        .filter(id.eq(todo_id))
+        .filter(deletion_date.is_null())
+        .set(completion_date.eq(&time))
+        .get_result::<Todo>(conn)?;
+    insert_event(
+        conn,
+        actor,
+        channel,
+        todo_id,
+        TodoAction::Completed,
+        None,
+        None,
+    )?;
+    let next = if was_completed {
+        None
+    } else {
+        add_next_occurrence(conn, actor, &completed)?
+    };
+    Ok((completed, next))
+}
+
+/// Brings GitHub issues and threads of the completed or uncompleted TODOs in
+/// line with them, assignees of TODOs no longer blocked are notified.
+async fn after_completed(
+    ctx_data: &CtxData,
+    http: &Http,
+    todo_keys: &[(i64, i32)],
+    completed: bool,
+) {
+    let state = if completed {
+        IssueState::Closed
+    } else {
+        IssueState::Open
+    };
+    for &(channel, todo_id) in todo_keys {
+        github::update_issue(ctx_data, channel, todo_id, state.clone()).await;
+    }
+    if completed {
+        dependency::notify_unblocked(ctx_data, http, todo_keys, &[]).await;
+    }
+    thread::set_archived(ctx_data, http, todo_keys, completed).await;
+}
+
+/// Adds the next occurrence of the completed TODO if it's recurring, carrying
+/// over its assignees, watchers, priority and tags. Returns ID of the new
+/// TODO.
+fn add_next_occurrence(
+    conn: &mut SqliteConnection,
+    actor: UserId,
+    completed: &Todo,
+) -> QueryResult<Option<i32>> {
+    use crate::schema::{
+        todo_tags::dsl::{channel_id, guild_id, tag, todo_id, todo_tags},
+        todos::dsl::todos,
+    };
+
+    let Some(recurrence) = completed
+        .recurrence
+        .as_deref()
+        .and_then(|r| r.parse::<Recurrence>().ok())
+    else {
+        return Ok(None);
+    };
+
+    let now = OffsetDateTime::now_utc();
+    let due = completed.due_date.as_deref().and_then(parse_time);
+    let Some(next_due) = recurrence.next_due(due, now) else {
+        return Ok(None);
+    };
+    let recurrence = recurrence.anchored(due.unwrap_or(now));
+
+    let channel = completed.channel_id;
+    let new_id = next_todo_id(conn, channel)?;
+    let time = now.format(&TIME_FORMAT).unwrap();
+
+    let new_todo = NewTodo {
+        channel_id: &channel,
+        id: &new_id,
+        todo: &completed.todo,
+        creation_date: &time,
+        completion_date: None,
+        priority: completed.priority,
+        due_date: Some(next_due.format(&TIME_FORMAT).unwrap()),
+        recurrence: Some(recurrence.to_string()),
+        issue: None,
+        message_link: completed.message_link.clone(),
+    };
+
+    diesel::insert_into(todos).values(&new_todo).execute(conn)?;
+
+    let assignees = get_todo_assignees(conn, channel, completed.id)?;
+    set_assignees(conn, channel, new_id, &assignees)?;
+    watch::copy_watchers(conn, channel, completed.id, new_id)?;
+
+    let tags = todo_tags
+        .filter(channel_id.eq(channel))
+        .filter(todo_id.eq(completed.id))
+        .select((guild_id, tag))
+        .load::<(i64, String)>(conn)?;
+
+    for (tag_guild, tag_name) in &tags {
+        let new_tag = NewTag {
+            channel_id: &channel,
+            todo_id: &new_id,
+            guild_id: tag_guild,
+            tag: tag_name,
+        };
+        diesel::insert_into(todo_tags)
+            .values(&new_tag)
+            .execute(conn)?;
+    }
+
+    insert_event(
+        conn,
+        actor,
+        channel,
+        new_id,
+        TodoAction::Created,
+        None,
+        Some(&completed.todo),
+    )?;
+
+    Ok(Some(new_id))
+}
+
+/// Uncomplete TODO entries
+#[poise::command(slash_command)]
+pub async fn uncomplete(
+    ctx: Context<'_>,
+    #[description = "TODO ids, e.g. `3,5,9-12`"] todo_ids: IdSelection,
+) -> Result<()> {
+    let channel = i64::from(ctx.channel_id());
+    let actor = ctx.author().id;
+
+    let uncompleted = ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+        change_todos(conn, todo_ids.ids(), |conn, todo_id| {
+            uncomplete_todo(conn, actor, channel, todo_id)
+        })
+    });
+
+    let ids = changed_ids(&uncompleted);
+    let reopened: Vec<(i64, i32)> = ids.iter().map(|todo_id| (channel, *todo_id)).collect();
+    after_completed(ctx.data(), ctx.http(), &reopened, false).await;
+
+    let data = format_changed(
+        uncompleted,
+        "Uncompleting TODO failed.",
+        |changed| match changed {
+            [(todo_id, content)] => MessageBuilder::new()
+                .push(format!("TODO [{todo_id}] ("))
+                .push_mono_safe(content)
+                .push(") uncompleted.")
+                .build(),
+            _ => format!("TODOs {} uncompleted.", format_ids(&ids)),
+        },
+    );
+
+    respond_text(ctx, data, true).await;
+
+    Ok(())
+}
+
+/// Uncompletes the TODO, has to be called in a transaction.
+fn uncomplete_todo(
+    conn: &mut SqliteConnection,
+    actor: UserId,
+    channel: i64,
+    todo_id: i32,
+) -> QueryResult<String> {
+    use crate::schema::todos::dsl::{channel_id, completion_date, deletion_date, id, todo, todos};
+
+    let uncompleted = diesel::update(todos)
+        .filter(channel_id.eq(channel))
+        .filter(id.eq(todo_id))
+        .filter(deletion_date.is_null())
+        .set(completion_date.eq::<Option<String>>(None))
+        .returning(todo)
+        .get_result::<String>(conn)?;
+    insert_event(
+        conn,
+        actor,
+        channel,
+        todo_id,
+        TodoAction::Uncompleted,
+        None,
+        None,
+    )?;
+    Ok(uncompleted)
+}
+
+/// Assign TODO entries
+#[poise::command(slash_command)]
+pub async fn assign(
+    ctx: Context<'_>,
+    #[description = "TODO ids, e.g. `3,5,9-12`"] todo_ids: IdSelection,
+    #[description = "TODO new assignees, e.g. `@alice @oncall`, no one if empty"]
+    new_assignees: Option<AssigneeSelection>,
+) -> Result<()> {
+    let new_assignees = new_assignees
+        .as_ref()
+        .map_or(&[][..], AssigneeSelection::assignees);
+
+    let mut nicknames = vec![];
+    for &assignee in new_assignees {
+        let Some(guild) = ctx.guild_id() else {
+            // There are no nicknames outside of a server
+            nicknames.push(assignee.mention().to_string());
+            continue;
+        };
+        let Some(name) = get_assignee_name(&ctx, guild, assignee).await else {
+            let data = format!("{} isn't in the server.", assignee.mention());
+            respond_text(ctx, data, true).await;
+            return Ok(());
+        };
+        nicknames.push(name);
+    }
+    let nickname = if nicknames.is_empty() {
+        "no one".to_string()
+    } else {
+        nicknames.join(", ")
+    };
+
+    let channel = i64::from(ctx.channel_id());
+    let actor = ctx.author().id;
+
+    let reassigned = ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+        change_todos(conn, todo_ids.ids(), |conn, todo_id| {
+            assign_todo(conn, actor, channel, todo_id, new_assignees)
+        })
+    });
+
+    let ids = changed_ids(&reassigned);
+    let data = format_changed(
+        reassigned,
+        "Assigning TODO failed.",
+        |changed| match changed {
+            [(todo_id, content)] => MessageBuilder::new()
+                .push(format!("TODO [{todo_id}] ("))
+                .push_mono_safe(content)
+                .push(format!(") reassigned to {nickname}."))
+                .build(),
+            _ => format!("TODOs {} reassigned to {nickname}.", format_ids(&ids)),
+        },
+    );
+
+    respond_text(ctx, data, true).await;
+
+    Ok(())
+}
+
+/// Replaces assignees of the TODO, has to be called in a transaction.
+fn assign_todo(
+    conn: &mut SqliteConnection,
+    actor: UserId,
+    channel: i64,
+    todo_id: i32,
+    new_assignees: &[Assignee],
+) -> QueryResult<String> {
+    use crate::schema::todos::dsl::{channel_id, deletion_date, id, todo, todos};
+
+    let reassigned = todos
+        .filter(channel_id.eq(channel))
+        .filter(id.eq(todo_id))
+        .filter(deletion_date.is_null())
+        .select(todo)
+        .first::<String>(conn)?;
+    let old_assignees = get_todo_assignees(conn, channel, todo_id)?;
+    set_assignees(conn, channel, todo_id, new_assignees)?;
+
+    let mentions = |assignees: &[Assignee]| {
+        (!assignees.is_empty()).then(|| assignees.iter().map(|a| a.mention()).join(", "))
+    };
+    insert_event(
+        conn,
+        actor,
+        channel,
+        todo_id,
+        TodoAction::Assigned,
+        mentions(&old_assignees).as_deref(),
+        mentions(new_assignees).as_deref(),
+    )?;
+    Ok(reassigned)
+}
+
+/// Returns assignees of the TODO, members before roles, ordered by their IDs.
+fn get_todo_assignees(
+    conn: &mut SqliteConnection,
+    channel: i64,
+    todo: i32,
+) -> QueryResult<Vec<Assignee>> {
+    use crate::schema::todo_assignees::dsl::{
+        assignee_id, channel_id, is_role, todo_assignees, todo_id,
+    };
+
+    let assignees = todo_assignees
+        .filter(channel_id.eq(channel))
+        .filter(todo_id.eq(todo))
+        .select((assignee_id, is_role))
+        .order((is_role.asc(), assignee_id.asc()))
+        .load::<(i64, bool)>(conn)?
+        .into_iter()
+        .map(|(id, role)| Assignee::from_row(id, role))
+        .collect();
+
+    Ok(assignees)
+}
+
+/// Replaces assignees of the TODO without recording it in the history, has to
+/// be called in a transaction.
+fn set_assignees(
+    conn: &mut SqliteConnection,
+    channel: i64,
+    todo: i32,
+    assignees: &[Assignee],
+) -> QueryResult<()> {
+    use crate::schema::todo_assignees::dsl::{
+        assignee_id, channel_id, is_role, todo_assignees, todo_id,
+    };
+
+    diesel::delete(todo_assignees)
+        .filter(channel_id.eq(channel))
+        .filter(todo_id.eq(todo))
+        .execute(conn)?;
+
+    let rows: Vec<_> = assignees
+        .iter()
+        .map(|assignee| {
+            (
+                channel_id.eq(channel),
+                todo_id.eq(todo),
+                assignee_id.eq(assignee.id()),
+                is_role.eq(assignee.is_role()),
+            )
+        })
+        .collect();
+    diesel::insert_into(todo_assignees)
+        .values(&rows)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Move TODO entries
+#[poise::command(slash_command, rename = "move")]
+pub async fn rmove(
+    ctx: Context<'_>,
+    #[description = "TODO ids, e.g. `3,5,9-12`"] todo_ids: IdSelection,
+    #[description = "TODO new channel"] new_channel: GuildChannel,
+) -> Result<()> {
+    if new_channel.id == ctx.channel_id() {
+        respond_text(ctx, "TODOs are already in the channel.".to_string(), true).await;
+        return Ok(());
+    }
+
+    let channel = i64::from(ctx.channel_id());
+    let new_channel_id = i64::from(new_channel.id);
+    let actor = ctx.author().id;
+
+    let moved = ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+        let issues = get_issues(conn, channel, todo_ids.ids())?;
+        let moved = transfer_todos(conn, todo_ids.ids(), |conn, todo_id| {
+            move_todo(conn, actor, channel, todo_id, new_channel_id)
+        })?;
+        Ok((moved, issues))
+    });
+    let moved = close_moved_issues(ctx, channel, moved).await;
+
+    let data = format_transferred(moved, "moved", &new_channel, "Moving TODO failed.");
+    respond_text(ctx, data, false).await;
+
+    Ok(())
+}
+
+/// Copy TODO entries
+#[poise::command(slash_command)]
+pub async fn copy(
+    ctx: Context<'_>,
+    #[description = "TODO ids, e.g. `3,5,9-12`"] todo_ids: IdSelection,
+    #[description = "Channel to copy TODOs to"] new_channel: GuildChannel,
+) -> Result<()> {
+    let channel = i64::from(ctx.channel_id());
+    let new_channel_id = i64::from(new_channel.id);
+    let actor = ctx.author().id;
+
+    let copied = ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+        transfer_todos(conn, todo_ids.ids(), |conn, todo_id| {
+            copy_todo(conn, actor, channel, todo_id, new_channel_id)
+        })
+    });
+
+    let data = format_transferred(copied, "copied", &new_channel, "Copying TODO failed.");
+    respond_text(ctx, data, false).await;
+
+    Ok(())
+}
+
+/// Move all TODO entries matching the filters
+#[poise::command(slash_command, rename = "move-all")]
+pub async fn move_all(
+    ctx: Context<'_>,
+    #[description = "TODO new channel"] new_channel: GuildChannel,
+    #[description = "Move only TODOs assigned to the member or their roles"] assignee: Option<
+        Member,
+    >,
+    #[description = "Move only TODOs with the priority"] priority: Option<Priority>,
+    #[description = "Move only completed (True) or only uncompleted (False) TODOs"]
+    completed: Option<bool>,
+) -> Result<()> {
+    use crate::schema::{
+        todo_assignees,
+        todos::dsl::{
+            channel_id, completion_date, deletion_date, id, priority as todo_priority, todos,
+        },
+    };
+
+    if new_channel.id == ctx.channel_id() {
+        respond_text(ctx, "TODOs are already in the channel.".to_string(), true).await;
+        return Ok(());
+    }
+
+    let channel = i64::from(ctx.channel_id());
+    let new_channel_id = i64::from(new_channel.id);
+    let actor = ctx.author().id;
+
+    // IDs are selected in the transaction, so that TODOs added meanwhile
+    // don't get left behind
+    let moved = ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+        let mut query = todos
+            .filter(channel_id.eq(channel))
+            .filter(deletion_date.is_null())
+            .select(id)
+            .order(id.asc())
+            .into_boxed();
+        if let Some(assignee) = &assignee {
+            query = query.filter(diesel::dsl::exists(
+                todo_assignees::table
+                    .filter(todo_assignees::channel_id.eq(channel_id))
+                    .filter(todo_assignees::todo_id.eq(id))
+                    .filter(is_assigned_to(assignee)),
+            ));
+        }
+        if let Some(priority) = priority {
+            query = query.filter(todo_priority.eq(priority as i32));
+        }
+        match completed {
+            Some(true) => query = query.filter(completion_date.is_not_null()),
+            Some(false) => query = query.filter(completion_date.is_null()),
+            None => {}
+        }
+        let todo_ids = query.load::<i32>(conn)?;
+
+        let issues = get_issues(conn, channel, &todo_ids)?;
+        let moved = transfer_todos(conn, &todo_ids, |conn, todo_id| {
+            move_todo(conn, actor, channel, todo_id, new_channel_id)
+        })?;
+        Ok((moved, issues))
+    });
+    let moved = close_moved_issues(ctx, channel, moved).await;
+
+    let data = match moved {
+        Ok((moved, _)) if moved.is_empty() => "There are no matching TODOs.".to_string(),
+        moved => format_transferred(moved, "moved", &new_channel, "Moving TODOs failed."),
+    };
+    respond_text(ctx, data, false).await;
+
+    Ok(())
+}
+
+/// Results for each of the changed TODOs, and IDs of TODOs that weren't found.
+type Changed<T> = (Vec<(i32, T)>, Vec<i32>);
+/// New IDs and contents of the moved or copied TODOs.
+type Transferred = Changed<(i32, String)>;
+
+/// Applies `change` to each of the TODOs, has to be called in a transaction.
+/// TODOs that aren't found are skipped, any other error stops it.
+fn change_todos<T>(
+    conn: &mut SqliteConnection,
+    todo_ids: &[i32],
+    mut change: impl FnMut(&mut SqliteConnection, i32) -> QueryResult<T>,
+) -> QueryResult<Changed<T>> {
+    let mut changed = vec![];
+    let mut missing = vec![];
+
+    for &todo_id in todo_ids {
+        match change(conn, todo_id) {
+            Ok(result) => changed.push((todo_id, result)),
+            Err(NotFound) => missing.push(todo_id),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok((changed, missing))
+}
+
+/// Returns IDs of the changed TODOs, none if changing them failed.
+fn changed_ids<T>(result: &QueryResult<Changed<T>>) -> Vec<i32> {
+    result.as_ref().map_or_else(
+        |_| vec![],
+        |(changed, _)| changed.iter().map(|(todo_id, _)| *todo_id).collect(),
+    )
+}
+
+/// Formats response of a command changing the TODOs, `format` describes the
+/// changed ones and TODOs that weren't found are listed after them.
+fn format_changed<T>(
+    result: QueryResult<Changed<T>>,
+    failed: &str,
+    format: impl FnOnce(&[(i32, T)]) -> String,
+) -> String {
+    match result {
+        Ok((changed, _)) if changed.is_empty() => "Not found.".to_string(),
+        Ok((changed, missing)) if missing.is_empty() => format(&changed),
+        Ok((changed, missing)) => {
+            format!("{} Not found: {}.", format(&changed), format_ids(&missing))
+        }
+        Err(DatabaseError(UniqueViolation, _)) => ID_CONFLICT.to_string(),
+        Err(_) => failed.to_string(),
+    }
+}
+
+/// Moves or copies each of the TODOs with `transfer`, which returns new ID and
+/// content of the TODO. Has to be called in a transaction.
+fn transfer_todos(
+    conn: &mut SqliteConnection,
+    todo_ids: &[i32],
+    mut transfer: impl FnMut(&mut SqliteConnection, i32) -> QueryResult<(i32, String)>,
+) -> QueryResult<Transferred> {
+    let mut created = HashSet::new();
+
+    change_todos(conn, todo_ids, |conn, todo_id| {
+        // TODOs copied to the same channel can get IDs which are selected too,
+        // they didn't exist when the command was run
+        if created.contains(&todo_id) {
+            return Err(NotFound);
+        }
+        let (new_id, content) = transfer(conn, todo_id)?;
+        created.insert(new_id);
+        Ok((new_id, content))
+    })
+}
+
+fn format_transferred(
+    result: QueryResult<Transferred>,
+    verb: &str,
+    new_channel: &GuildChannel,
+    failed: &str,
+) -> String {
+    format_changed(result, failed, |transferred| {
+        if let [(todo_id, (new_id, content))] = transferred {
+            return MessageBuilder::new()
+                .push(format!("TODO [{todo_id}] ("))
+                .push_mono_safe(content)
+                .push(format!(
+                    ") {verb} to {} as TODO [{new_id}].",
+                    new_channel.name()
+                ))
+                .build();
+        }
+
+        let ids = transferred
+            .iter()
+            .take(MAX_LISTED_TRANSFERS)
+            .map(|(todo_id, (new_id, _))| format!("[{todo_id}] → [{new_id}]"))
+            .join(", ");
+        let mut text = format!(
+            "{} TODOs {verb} to {}: {ids}",
+            transferred.len(),
+            new_channel.name()
+        );
+        if transferred.len() > MAX_LISTED_TRANSFERS {
+            text = format!(
+                "{text} and {} more",
+                transferred.len() - MAX_LISTED_TRANSFERS
+            );
+        }
+        format!("{text}.")
+    })
+}
+
+/// Returns numbers of GitHub issues linked to the TODOs.
+fn get_issues(
+    conn: &mut SqliteConnection,
+    channel: i64,
+    todo_ids: &[i32],
+) -> QueryResult<Vec<i32>> {
+    use crate::schema::todos::dsl::{channel_id, deletion_date, id, issue, todos};
+
+    todos
+        .filter(channel_id.eq(channel))
+        .filter(id.eq_any(todo_ids))
+        .filter(deletion_date.is_null())
+        .filter(issue.is_not_null())
+        .select(issue.assume_not_null())
+        .load::<i32>(conn)
+}
+
+/// Closes issues of the moved TODOs, they are unlinked from them as the issues
+/// belong to the repository of the old channel. Left open they would be added
+/// to the old channel again by the sync.
+async fn close_moved_issues(
+    ctx: Context<'_>,
+    channel: i64,
+    moved: QueryResult<(Transferred, Vec<i32>)>,
+) -> QueryResult<Transferred> {
+    let (moved, issues) = moved?;
+    for number in issues {
+        github::close_issue(ctx.data(), channel, number).await;
+    }
+    Ok(moved)
+}
+
+/// Moves the TODO with its history to the channel, returns its new ID and
+/// content.
+fn move_todo(
+    conn: &mut SqliteConnection,
+    actor: UserId,
+    channel: i64,
+    todo_id: i32,
+    new_channel: i64,
+) -> QueryResult<(i32, String)> {
+    use crate::schema::{
+        todo_events,
+        todos::dsl::{channel_id, deletion_date, id, issue, todo, todos},
+    };
+
+    let new_id = next_todo_id(conn, new_channel)?;
+    let moved = diesel::update(todos)
+        .filter(channel_id.eq(channel))
+        .filter(id.eq(todo_id))
+        .filter(deletion_date.is_null())
+        // Issue belongs to the repository linked to the old channel
+        .set((
+            channel_id.eq(new_channel),
+            id.eq(new_id),
+            issue.eq::<Option<i32>>(None),
+        ))
+        .returning(todo)
+        .get_result::<String>(conn)?;
+    // History follows the TODO to its new place
+    diesel::update(todo_events::table)
+        .filter(todo_events::channel_id.eq(channel))
+        .filter(todo_events::todo_id.eq(todo_id))
+        .set((
+            todo_events::channel_id.eq(new_channel),
+            todo_events::todo_id.eq(new_id),
+        ))
+        .execute(conn)?;
+    let old_place = format!("<#{channel}> [{todo_id}]");
+    let new_place = format!("<#{new_channel}> [{new_id}]");
+    insert_event(
+        conn,
+        actor,
+        new_channel,
+        new_id,
+        TodoAction::Moved,
+        Some(&old_place),
+        Some(&new_place),
+    )?;
+    // Old channel gets the event as well so its board notices the TODO left,
+    // IDs aren't reused so it can't end up in history of another TODO
+    insert_event(
+        conn,
+        actor,
+        channel,
+        todo_id,
+        TodoAction::Moved,
+        Some(&old_place),
+        Some(&new_place),
+    )?;
+
+    Ok((new_id, moved))
+}
+
+/// Copies the TODO with its assignees, tags and checklist to the channel,
+/// returns ID and content of the copy.
+fn copy_todo(
+    conn: &mut SqliteConnection,
+    actor: UserId,
+    channel: i64,
+    todo_id: i32,
+    new_channel: i64,
+) -> QueryResult<(i32, String)> {
+    use crate::schema::{
+        todo_checklist_items::dsl::{
+            channel_id as item_channel_id, checked, id as item_id, text as item_text,
+            todo_checklist_items, todo_id as item_todo_id,
+        },
+        todo_tags::dsl::{
+            channel_id as tag_channel_id, guild_id, tag, todo_id as tag_todo_id, todo_tags,
+        },
+        todos::dsl::{channel_id, deletion_date, id, todos},
+    };
+
+    let original = todos
+        .filter(channel_id.eq(channel))
+        .filter(id.eq(todo_id))
+        .filter(deletion_date.is_null())
+        .first::<Todo>(conn)?;
+
+    let new_id = next_todo_id(conn, new_channel)?;
+    let time = OffsetDateTime::now_utc().format(&TIME_FORMAT).unwrap();
+    let new_todo = NewTodo {
+        channel_id: &new_channel,
+        id: &new_id,
+        todo: &original.todo,
+        creation_date: &time,
+        completion_date: original.completion_date.clone(),
+        priority: original.priority,
+        due_date: original.due_date.clone(),
+        recurrence: original.recurrence.clone(),
+        issue: None,
+        message_link: original.message_link.clone(),
+    };
+    diesel::insert_into(todos).values(&new_todo).execute(conn)?;
+
+    let assignees = get_todo_assignees(conn, channel, todo_id)?;
+    set_assignees(conn, new_channel, new_id, &assignees)?;
+
+    let tags = todo_tags
+        .filter(tag_channel_id.eq(channel))
+        .filter(tag_todo_id.eq(todo_id))
+        .select((guild_id, tag))
+        .load::<(i64, String)>(conn)?;
+    for (tag_guild, tag_name) in &tags {
+        let new_tag = NewTag {
+            channel_id: &new_channel,
+            todo_id: &new_id,
+            guild_id: tag_guild,
+            tag: tag_name,
+        };
+        diesel::insert_into(todo_tags)
+            .values(&new_tag)
+            .execute(conn)?;
+    }
+
+    let items = todo_checklist_items
+        .filter(item_channel_id.eq(channel))
+        .filter(item_todo_id.eq(todo_id))
+        .select((item_id, item_text, checked))
+        .load::<ChecklistItem>(conn)?;
+    for item in &items {
+        let new_item = NewChecklistItem {
+            channel_id: &new_channel,
+            todo_id: &new_id,
+            id: &item.id,
+            text: &item.text,
+        };
+        diesel::insert_into(todo_checklist_items)
+            .values(&new_item)
+            .execute(conn)?;
+        if item.checked {
+            diesel::update(todo_checklist_items)
+                .filter(item_channel_id.eq(new_channel))
+                .filter(item_todo_id.eq(new_id))
+                .filter(item_id.eq(item.id))
+                .set(checked.eq(true))
+                .execute(conn)?;
+        }
+    }
+
+    insert_event(
+        conn,
+        actor,
+        new_channel,
+        new_id,
+        TodoAction::Copied,
+        Some(&format!("<#{channel}> [{todo_id}]")),
+        Some(&format!("<#{new_channel}> [{new_id}]")),
+    )?;
+
+    Ok((new_id, original.todo))
+}
+
+/// Edit TODO entry
+#[poise::command(slash_command)]
+pub async fn edit(
+    ctx: Context<'_>,
+    #[description = "TODO id"] todo_id: i64,
+    #[description = "TODO new content"] content: Option<String>,
+    #[description = "TODO new due date, `none` removes it"] due: Option<String>,
+    #[description = "TODO new recurrence, `none` removes it"] recurrence: Option<String>,
+) -> Result<()> {
+    let new_due_date = due.as_deref().map(|due| {
+        if due.trim().eq_ignore_ascii_case("none") {
+            Some(None)
+        } else {
+            parse_due_date(due).map(Some)
+        }
+    });
+    let new_recurrence = recurrence.as_deref().map(|recurrence| {
+        if recurrence.trim().eq_ignore_ascii_case("none") {
+            Ok(None)
+        } else {
+            recurrence
+                .parse::<Recurrence>()
+                .map(|r| Some(r.to_string()))
+        }
+    });
+
+    let data = if content.is_none() && due.is_none() && recurrence.is_none() {
+        "Nothing to edit.".to_string()
+    } else if content.as_ref().is_some_and(|content| content.len() > 1024) {
+        "Content can't have more than 1024 characters.".to_string()
+    } else if let Some(None) = new_due_date {
+        "Invalid due date.".to_string()
+    } else if let Some(Err(e)) = new_recurrence {
+        format!("Invalid recurrence, {e}.")
+    } else {
+        let edited = edit_todo(
+            &mut ctx.data().db.get().unwrap(),
+            ctx.author().id,
+            i64::from(ctx.channel_id()),
+            todo_id as i32,
+            content,
+            new_due_date.flatten(),
+            new_recurrence.and_then(std::result::Result::ok),
+        );
+
+        match edited {
+            Ok((edited, edited_due, edited_recurrence)) => {
+                let due = edited_due.map_or_else(|| "no".to_string(), |due| format!("`{due}`"));
+                let repeats = edited_recurrence.map_or_else(
+                    || "doesn't repeat".to_string(),
+                    |recurrence| format!("repeats `{recurrence}`"),
+                );
+                MessageBuilder::new()
+                    .push(format!("TODO [{todo_id}] edited to ("))
+                    .push_mono_safe(&edited)
+                    .push(format!(") with {due} due date, {repeats}."))
+                    .build()
+            }
+            Err(NotFound) => "Not found.".to_string(),
+            Err(_) => "Editing TODO failed.".to_string(),
+        }
+    };
+
+    respond_text(ctx, data, true).await;
+
+    Ok(())
+}
+
+// Zero width space inserted after `@` so that edited TODOs don't mention
+const MENTION_ESCAPE: &str = "@\u{200B}";
+
+/// Breaks mentions and code blocks in the TODO text, already escaped text
+/// stays as it is.
+fn escape_text(text: &str) -> String {
+    unescape_text(text)
+        .replace('@', MENTION_ESCAPE)
+        .replace('`', "'")
+}
+
+/// Restores mentions broken by `escape_text`.
+fn unescape_text(text: &str) -> String {
+    text.replace(MENTION_ESCAPE, "@")
+}
+
+/// Changes the given fields of the TODO, `None` leaves the field as it is.
+#[allow(clippy::option_option)]
+fn edit_todo(
+    conn: &mut SqliteConnection,
+    actor: UserId,
+    channel: i64,
+    todo_id: i32,
+    content: Option<String>,
+    new_due_date: Option<Option<String>>,
+    new_recurrence: Option<Option<String>>,
+) -> QueryResult<(String, Option<String>, Option<String>)> {
+    use crate::schema::todos::dsl::{
+        channel_id, deletion_date, due_date, id, recurrence as recurrence_column, todo, todos,
+    };
+
+    let text = content.map(|content| escape_text(&content));
+
+    conn.immediate_transaction(|conn| {
+        let (old_text, old_due, old_recurrence) = todos
+            .filter(channel_id.eq(channel))
+            .filter(id.eq(todo_id))
+            .filter(deletion_date.is_null())
+            .select((todo, due_date, recurrence_column))
+            .first::<(String, Option<String>, Option<String>)>(conn)?;
+        let (new_text, new_due, new_recurrence) = diesel::update(todos)
+            .filter(channel_id.eq(channel))
+            .filter(id.eq(todo_id))
+            .filter(deletion_date.is_null())
+            .set((
+                text.map(|text| todo.eq(text)),
+                new_due_date.map(|new_due| due_date.eq(new_due)),
+                new_recurrence.map(|new_recurrence| recurrence_column.eq(new_recurrence)),
+            ))
+            .returning((todo, due_date, recurrence_column))
+            .get_result::<(String, Option<String>, Option<String>)>(conn)?;
+
+        if old_text != new_text {
+            insert_event(
+                conn,
+                actor,
+                channel,
+                todo_id,
+                TodoAction::Edited,
+                Some(&old_text),
+                Some(&new_text),
+            )?;
+        }
+        if old_due != new_due {
+            insert_event(
+                conn,
+                actor,
+                channel,
+                todo_id,
+                TodoAction::ChangedDueDate,
+                old_due.as_deref(),
+                new_due.as_deref(),
+            )?;
+        }
+        if old_recurrence != new_recurrence {
+            insert_event(
+                conn,
+                actor,
+                channel,
+                todo_id,
+                TodoAction::ChangedRecurrence,
+                old_recurrence.as_deref(),
+                new_recurrence.as_deref(),
+            )?;
+        }
+
+        Ok((new_text, new_due, new_recurrence))
+    })
+}
+
+/// Change priority of TODO entries
+#[poise::command(slash_command)]
+pub async fn set_priority(
+    ctx: Context<'_>,
+    #[description = "TODO ids, e.g. `3,5,9-12`"] todo_ids: IdSelection,
+    #[description = "TODO new priority"] new_priority: Option<Priority>,
+) -> Result<()> {
+    let new_priority = new_priority.unwrap_or_default();
+    let channel = i64::from(ctx.channel_id());
+    let actor = ctx.author().id;
+
+    let reprioritized = ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+        change_todos(conn, todo_ids.ids(), |conn, todo_id| {
+            set_todo_priority(conn, actor, channel, todo_id, new_priority)
+        })
+    });
+
+    let ids = changed_ids(&reprioritized);
+    let data = format_changed(
+        reprioritized,
+        "Assigning TODO failed.",
+        |changed| match changed {
+            [(todo_id, content)] => MessageBuilder::new()
+                .push(format!("TODO [{todo_id}] ("))
+                .push_mono_safe(content)
+                .push(format!(") new priority is {new_priority}."))
+                .build(),
+            _ => format!("TODOs {} new priority is {new_priority}.", format_ids(&ids)),
+        },
+    );
+
+    respond_text(ctx, data, true).await;
+
+    Ok(())
+}
+
+/// Sets priority of the TODO, has to be called in a transaction.
+fn set_todo_priority(
+    conn: &mut SqliteConnection,
+    actor: UserId,
+    channel: i64,
+    todo_id: i32,
+    new_priority: Priority,
+) -> QueryResult<String> {
+    use crate::schema::todos::dsl::{channel_id, deletion_date, id, priority, todo, todos};
+
+    let old_priority = todos
+        .filter(channel_id.eq(channel))
+        .filter(id.eq(todo_id))
+        .filter(deletion_date.is_null())
+        .select(priority)
+        .first::<i32>(conn)?;
+    let reassigned = diesel::update(todos)
+        .filter(channel_id.eq(channel))
+        .filter(id.eq(todo_id))
+        .filter(deletion_date.is_null())
+        .set(priority.eq(new_priority as i32))
+        .returning(todo)
+        .get_result::<String>(conn)?;
+    insert_event(
+        conn,
+        actor,
+        channel,
+        todo_id,
+        TodoAction::ChangedPriority,
+        Some(&Priority::from(old_priority).to_string()),
+        Some(&new_priority.to_string()),
+    )?;
+    Ok(reassigned)
+}
+
+/// Show history of changes of TODO entry
+#[poise::command(slash_command)]
+pub async fn history(ctx: Context<'_>, #[description = "TODO id"] todo_id: i64) -> Result<()> {
+    use crate::schema::todo_events::dsl::{channel_id, id, todo_events, todo_id as event_todo_id};
+
+    let channel = i64::from(ctx.channel_id());
+    let conn = &mut ctx.data().db.get().unwrap();
+    let events = todo_events
+        .filter(channel_id.eq(channel))
+        .filter(event_todo_id.eq(todo_id as i32))
+        .order(id.desc())
+        .limit(HISTORY_LIMIT as i64)
+        .load::<Event>(conn);
+    let total = todo_events
+        .filter(channel_id.eq(channel))
+        .filter(event_todo_id.eq(todo_id as i32))
+        .count()
+        .get_result::<i64>(conn);
+
+    let data = match events.and_then(|events| Ok((events, total?))) {
+        Ok((events, _)) if events.is_empty() => "Not found.".to_string(),
+        Ok((events, total)) => {
+            let mut msg = MessageBuilder::new();
+            msg.push_bold_line(format!("History of TODO [{todo_id}]"));
+
+            for event in events.iter().rev() {
+                let date =
+                    parse_time(&event.creation_date).map_or(0, OffsetDateTime::unix_timestamp);
+                msg.push(format!("<t:{date}:f> "));
+                push_change(&mut msg, event);
+                msg.push_line("");
+            }
+
+            if total > HISTORY_LIMIT as i64 {
+                msg.push_italic_line(format!("Showing last {HISTORY_LIMIT} of {total} changes."));
+            }
+
+            limit_description(msg.build())
+        }
+        Err(_) => "Showing TODO history failed.".to_string(),
+    };
+
+    respond_text(ctx, data, true).await;
+
+    Ok(())
+}
+
+/// Describes the change recorded in the history, e.g. `@alice completed`.
+fn push_change(msg: &mut MessageBuilder, event: &Event) {
+    msg.push(format!(
+        "<@{}> {}",
+        event.actor,
+        event.action.replace('_', " ")
+    ));
+
+    let values = [&event.old_value, &event.new_value].into_iter().flatten();
+    for (i, value) in values.enumerate() {
+        msg.push(if i == 0 { ": " } else { " → " });
+        if event
+            .action
+            .parse::<TodoAction>()
+            .is_ok_and(TodoAction::has_mentions)
+        {
+            msg.push(value);
+        } else if value.chars().count() > MAX_CHANGE_VALUE_LENGTH {
+            let shortened: String = value.chars().take(MAX_CHANGE_VALUE_LENGTH - 1).collect();
+            msg.push_mono_safe(format!("{shortened}…"));
+        } else {
+            msg.push_mono_safe(value);
+        }
+    }
+}
+
+/// Shortens the text to fit in the embed description.
+fn limit_description(text: String) -> String {
+    if text.chars().count() <= DISCORD_EMBED_DESCRIPTION_LIMIT {
+        return text;
+    }
+
+    let mut shortened: String = text
+        .chars()
+        .take(DISCORD_EMBED_DESCRIPTION_LIMIT - 1)
+        .collect();
+    shortened.push('…');
+    shortened
+}
+
+/// Allocates the next TODO ID in the channel, has to be called in the
+/// transaction inserting the TODO. IDs taken by TODOs inserted outside of the
+/// sequence are skipped, so the insert conflicts only if that happens
+/// concurrently.
+fn next_todo_id(conn: &mut SqliteConnection, channel: i64) -> QueryResult<i32> {
+    use crate::schema::{
+        todo_sequences::dsl::{channel_id as sequence_channel_id, next_id, todo_sequences},
+        todos::dsl::{channel_id, id, todos},
+    };
+
+    let sequence_id = todo_sequences
+        .filter(sequence_channel_id.eq(channel))
+        .select(next_id)
+        .first::<i32>(conn)
+        .optional()?;
+    let max_id = todos
+        .filter(channel_id.eq(channel))
+        .select(diesel::dsl::max(id))
+        .first::<Option<i32>>(conn)?;
+
+    let new_id = sequence_id
+        .unwrap_or(1)
+        .max(max_id.map_or(1, |max_id| max_id + 1));
+
+    diesel::insert_into(todo_sequences)
+        .values((sequence_channel_id.eq(channel), next_id.eq(new_id + 1)))
+        .on_conflict(sequence_channel_id)
+        .do_update()
+        .set(next_id.eq(new_id + 1))
+        .execute(conn)?;
+
+    Ok(new_id)
+}
+
+/// Records a change of the TODO in its history, the author of the command is
+/// the actor.
+fn log_event(
+    conn: &mut SqliteConnection,
+    ctx: Context<'_>,
+    channel: i64,
+    todo: i32,
+    action: TodoAction,
+    old_value: Option<&str>,
+    new_value: Option<&str>,
+) -> QueryResult<()> {
+    insert_event(
+        conn,
+        ctx.author().id,
+        channel,
+        todo,
+        action,
+        old_value,
+        new_value,
+    )
+}
+
+/// Records the change made by `actor`, who doesn't have to be a command
+/// author, e.g. the bot itself when syncing.
+fn insert_event(
+    conn: &mut SqliteConnection,
+    actor: UserId,
+    channel: i64,
+    todo: i32,
+    action: TodoAction,
+    old_value: Option<&str>,
+    new_value: Option<&str>,
+) -> QueryResult<()> {
+    use crate::schema::todo_events::dsl::todo_events;
+
+    let time = OffsetDateTime::now_utc().format(&TIME_FORMAT).unwrap();
+    let action = action.to_string();
+
+    let new_event = NewEvent {
+        channel_id: &channel,
+        todo_id: &todo,
+        actor: &(actor.0 as i64),
+        action: &action,
+        old_value,
+        new_value,
+        creation_date: &time,
+    };
+
+    diesel::insert_into(todo_events)
+        .values(&new_event)
+        .execute(conn)
+        .map(|_| ())
+}
+
+/// Set personal reminder about TODO entry
+#[poise::command(slash_command)]
+pub async fn remind(
+    ctx: Context<'_>,
+    #[description = "TODO id"] todo_id: i64,
+    #[description = "When to remind, e.g. `in 2 hours` or `2024-05-01 14:00`"] when: String,
+) -> Result<()> {
+    use crate::schema::{
+        todo_reminders::dsl::todo_reminders,
+        todos::dsl::{channel_id, deletion_date, id, todo, todos},
+    };
+
+    let now = OffsetDateTime::now_utc();
+
+    let data = match utils::parse_date(&when, now) {
+        None => "Invalid reminder date.".to_string(),
+        Some(date) if date <= now => "Reminder date has to be in the future.".to_string(),
+        Some(date) => {
+            let conn = &mut ctx.data().db.get().unwrap();
+            let time = now.format(&TIME_FORMAT).unwrap();
+            let remind_date = date.format(&TIME_FORMAT).unwrap();
+
+            let result: QueryResult<String> = todos
+                .filter(channel_id.eq(i64::from(ctx.channel_id())))
+                .filter(id.eq(todo_id as i32))
+                .filter(deletion_date.is_null())
+                .select(todo)
+                .first(conn)
+                .and_then(|text| {
+                    let new_reminder = NewReminder {
+                        channel_id: &(i64::from(ctx.channel_id())),
+                        todo_id: &(todo_id as i32),
+                        guild_id: ctx.guild_id().map(i64::from),
+                        requester: &(ctx.author().id.0 as i64),
+                        remind_date: &remind_date,
+                        creation_date: &time,
+                    };
+
+                    diesel::insert_into(todo_reminders)
+                        .values(&new_reminder)
+                        .execute(conn)
+                        .map(|_| text)
+                });
+
+            match result {
+                Ok(text) => MessageBuilder::new()
+                    .push(format!("Reminder about TODO [{todo_id}] ("))
+                    .push_mono_safe(&text)
+                    .push(format!(") set for <t:{}:f>.", date.unix_timestamp()))
+                    .build(),
+                Err(NotFound) => "Not found.".to_string(),
+                Err(_) => "Setting reminder failed.".to_string(),
+            }
+        }
+    };
+
+    respond_text(ctx, data, true).await;
+
+    Ok(())
+}
+
+/// Configure periodic TODO reminders in the channel
+#[poise::command(slash_command, required_permissions = "MANAGE_CHANNELS")]
+pub async fn reminders(
+    ctx: Context<'_>,
+    #[description = "Send reminders in the channel"] enabled: Option<bool>,
+    #[description = "Days between reminders"]
+    #[min = 1]
+    interval: Option<u32>,
+    #[description = "Time of day (UTC) to send reminders at, e.g. 09:00"] time: Option<String>,
+    #[description = "Days without reminders, e.g. `sat,sun`"] quiet_days: Option<String>,
+    #[description = "Revert to the default configuration"]
+    #[flag]
+    reset: bool,
+) -> Result<()> {
+    use crate::schema::todo_channels::dsl::{
+        channel_id, reminder_interval, reminder_quiet_days, reminder_time, reminders_enabled,
+        todo_channels,
+    };
+
+    let time = time.map(|t| utils::parse_time_of_day(&t).map(utils::format_time_of_day));
+    let quiet_days =
+        quiet_days.map(|d| utils::parse_weekdays(&d).map(|d| utils::format_weekdays(&d)));
+
+    if let Some(None) = time {
+        respond_text(ctx, "Invalid time of day.".to_string(), true).await;
+        return Ok(());
+    }
+    if let Some(None) = quiet_days {
+        respond_text(ctx, "Invalid quiet days.".to_string(), true).await;
+        return Ok(());
+    }
+
+    let channel = i64::from(ctx.channel_id());
+
+    let result = if reset {
+        diesel::update(todo_channels.filter(channel_id.eq(channel)))
+            .set((
+                reminders_enabled.eq::<Option<bool>>(None),
+                reminder_interval.eq::<Option<i32>>(None),
+                reminder_time.eq::<Option<String>>(None),
+                reminder_quiet_days.eq::<Option<String>>(None),
+            ))
+            .execute(&mut ctx.data().db.get().unwrap())
+    } else if enabled.is_some() || interval.is_some() || time.is_some() || quiet_days.is_some() {
+        let new_settings = NewChannelSettings {
+            channel_id: &channel,
+            reminders_enabled: enabled,
+            reminder_interval: interval.map(|i| i as i32),
+            reminder_time: time.flatten(),
+            reminder_quiet_days: quiet_days.flatten(),
+            create_threads: None,
+        };
+
+        diesel::insert_into(todo_channels)
+            .values(&new_settings)
+            .on_conflict(channel_id)
+            .do_update()
+            .set(&new_settings)
+            .execute(&mut ctx.data().db.get().unwrap())
+    } else {
+        Ok(0)
+    };
+
+    let overrides = todo_channels
+        .filter(channel_id.eq(channel))
+        .first::<ChannelSettings>(&mut ctx.data().db.get().unwrap())
+        .optional();
+
+    let data = match result.and(overrides) {
+        Ok(overrides) => {
+            let settings = ctx
+                .data()
+                .settings
+                .get_bot_settings(ctx, &ctx.channel_id())
+                .await;
+            let schedule = ReminderSchedule::new(
+                &settings.todo_reminders,
+                ctx.channel_id(),
+                overrides.as_ref(),
+            );
+            format!("TODO reminders in this channel are {schedule}.")
+        }
+        Err(_) => "Configuring reminders failed.".to_string(),
+    };
+
+    respond_text(ctx, data, true).await;
+
+    Ok(())
+}
+
+/// Manage TODO checklist
+#[allow(clippy::unused_async)]
+#[poise::command(
+    slash_command,
+    subcommands("check_add", "check_toggle", "check_remove")
+)]
+pub async fn check(_ctx: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// Add checklist item to TODO entry
+#[poise::command(slash_command, rename = "add")]
+pub async fn check_add(
+    ctx: Context<'_>,
+    #[description = "TODO id"] todo_id: i64,
+    #[description = "Checklist item content"]
+    #[max_length = 256]
+    text: String,
+) -> Result<()> {
+    use crate::schema::{
+        todo_checklist_items::dsl::{
+            channel_id as item_channel_id, id as item_id, todo_checklist_items,
+            todo_id as item_todo_id,
+        },
+        todos::dsl::{channel_id, deletion_date, id, todos},
     };
 
-    respond_text(ctx, data, true).await;
+    let channel = i64::from(ctx.channel_id());
+    let todo = todo_id as i32;
 
-    Ok(())
-}
+    let result: QueryResult<i32> = ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+        todos
+            .filter(channel_id.eq(channel))
+            .filter(id.eq(todo))
+            .filter(deletion_date.is_null())
+            .select(id)
+            .first::<i32>(conn)?;
 
-/// Complete TODO entry
-#[poise::command(slash_command)]
-pub async fn complete(ctx: Context<'_>, #[description = "TODO id"] todo_id: i64) -> Result<()> {
-    use crate::schema::todos::dsl::{channel_id, completion_date, id, todo, todos};
+        let new_id = todo_checklist_items
+            .filter(item_channel_id.eq(channel))
+            .filter(item_todo_id.eq(todo))
+            .select(diesel::dsl::max(item_id))
+            .first::<Option<i32>>(conn)?
+            .map_or(1, |max| max + 1);
 
-    let time = OffsetDateTime::now_utc().format(&TIME_FORMAT).unwrap();
+        let new_item = NewChecklistItem {
+            channel_id: &channel,
+            todo_id: &todo,
+            id: &new_id,
+            text: &text,
+        };
 
-    let completed: QueryResult<String> = diesel::update(todos)
-        .filter(channel_id.eq(i64::from(ctx.channel_id())))
-        .filter(id.eq(todo_id as i32))
-        .set(completion_date.eq(&time.to_string()))
-        .returning(todo)
-        .get_result(&mut ctx.data().db.get().unwrap());
+        diesel::insert_into(todo_checklist_items)
+            .values(&new_item)
+            .execute(conn)?;
 
-    let data = match completed {
-        Ok(completed) => MessageBuilder::new()
-            .push(format!("TODO [{}] (", &todo_id))
-            .push_mono_safe(&completed)
-            .push(") completed.")
-            .build(),
+        log_event(
+            conn,
+            ctx,
+            channel,
+            todo,
+            TodoAction::AddedChecklistItem,
+            None,
+            Some(&text),
+        )?;
+
+        Ok(new_id)
+    });
+
+    let data = match result {
+        Ok(new_id) => {
+            let header = MessageBuilder::new()
+                .push(format!("Item [{new_id}] ("))
+                .push_mono_safe(&text)
+                .push(format!(") added to TODO [{todo_id}]."))
+                .build();
+            format_checklist(ctx, todo, header)
+        }
         Err(NotFound) => "Not found.".to_string(),
-        Err(_) => "Completing TODO failed.".to_string(),
+        Err(_) => "Adding checklist item failed.".to_string(),
     };
 
     respond_text(ctx, data, false).await;
@@ -391,66 +3054,110 @@ pub async fn complete(ctx: Context<'_>, #[description = "TODO id"] todo_id: i64)
     Ok(())
 }
 
-/// Uncomplete TODO entry
-#[poise::command(slash_command)]
-pub async fn uncomplete(ctx: Context<'_>, #[description = "TODO id"] todo_id: i64) -> Result<()> {
-    use crate::schema::todos::dsl::{channel_id, completion_date, id, todo, todos};
+/// Check or uncheck checklist item of TODO entry
+#[poise::command(slash_command, rename = "toggle")]
+pub async fn check_toggle(
+    ctx: Context<'_>,
+    #[description = "TODO id"] todo_id: i64,
+    #[description = "Checklist item id"] item: i64,
+) -> Result<()> {
+    use crate::schema::todo_checklist_items::dsl::{
+        channel_id, checked, id, text as item_text, todo_checklist_items, todo_id as item_todo_id,
+    };
 
-    let uncompleted: QueryResult<String> = diesel::update(todos)
-        .filter(channel_id.eq(i64::from(ctx.channel_id())))
-        .filter(id.eq(todo_id as i32))
-        .set(completion_date.eq::<Option<String>>(None))
-        .returning(todo)
-        .get_result(&mut ctx.data().db.get().unwrap());
+    let todo = todo_id as i32;
 
-    let data = match uncompleted {
-        Ok(uncompleted) => MessageBuilder::new()
-            .push(format!("TODO [{}] (", &todo_id))
-            .push_mono_safe(&uncompleted)
-            .push(") uncompleted.")
-            .build(),
+    let channel = i64::from(ctx.channel_id());
+
+    let toggled: QueryResult<(String, bool)> =
+        ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+            let (text, is_checked) = diesel::update(todo_checklist_items)
+                .filter(channel_id.eq(channel))
+                .filter(item_todo_id.eq(todo))
+                .filter(id.eq(item as i32))
+                .set(checked.eq(diesel::dsl::not(checked)))
+                .returning((item_text, checked))
+                .get_result::<(String, bool)>(conn)?;
+            let action = if is_checked {
+                TodoAction::CheckedChecklistItem
+            } else {
+                TodoAction::UncheckedChecklistItem
+            };
+            log_event(conn, ctx, channel, todo, action, None, Some(&text))?;
+            Ok((text, is_checked))
+        });
+
+    let data = match toggled {
+        Ok((text, is_checked)) => {
+            let state = if is_checked { "checked" } else { "unchecked" };
+            let mut header = MessageBuilder::new()
+                .push(format!("Item [{item}] ("))
+                .push_mono_safe(&text)
+                .push(format!(") of TODO [{todo_id}] {state}."))
+                .build();
+
+            if is_checked && complete_with_checklist(ctx, todo).await {
+                let completed = [(i64::from(ctx.channel_id()), todo)];
+                after_completed(ctx.data(), ctx.http(), &completed, true).await;
+                header = format!("{header}\nAll items are checked, TODO [{todo_id}] completed.");
+            }
+
+            format_checklist(ctx, todo, header)
+        }
         Err(NotFound) => "Not found.".to_string(),
-        Err(_) => "Uncompleting TODO failed.".to_string(),
+        Err(_) => "Toggling checklist item failed.".to_string(),
     };
 
-    respond_text(ctx, data, true).await;
+    respond_text(ctx, data, false).await;
 
     Ok(())
 }
 
-/// Assign TODO entry
-#[poise::command(slash_command)]
-pub async fn assign(
+/// Remove checklist item from TODO entry
+#[poise::command(slash_command, rename = "remove")]
+pub async fn check_remove(
     ctx: Context<'_>,
     #[description = "TODO id"] todo_id: i64,
-    #[description = "TODO new assignee"] new_assignee: Option<Member>,
+    #[description = "Checklist item id"] item: i64,
 ) -> Result<()> {
-    use crate::{
-        schema::todos::dsl::{assignee, channel_id, id, todo, todos},
-        utils,
+    use crate::schema::todo_checklist_items::dsl::{
+        channel_id, id, text as item_text, todo_checklist_items, todo_id as item_todo_id,
     };
 
-    let nickname = match &new_assignee {
-        Some(m) => utils::get_nick_from_member(m),
-        None => "no one".to_string(),
-    };
-    let new_assignee = new_assignee.map(|m| m.user.id.0 as i64);
+    let todo = todo_id as i32;
 
-    let reassigned: QueryResult<String> = diesel::update(todos)
-        .filter(channel_id.eq(i64::from(ctx.channel_id())))
-        .filter(id.eq(todo_id as i32))
-        .set(assignee.eq(new_assignee))
-        .returning(todo)
-        .get_result(&mut ctx.data().db.get().unwrap());
+    let channel = i64::from(ctx.channel_id());
 
-    let data = match reassigned {
-        Ok(reassigned) => MessageBuilder::new()
-            .push(format!("TODO [{}] (", &todo_id))
-            .push_mono_safe(&reassigned)
-            .push(format!(") reassigned to {nickname}."))
-            .build(),
+    let removed: QueryResult<String> = ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+        let removed = diesel::delete(todo_checklist_items)
+            .filter(channel_id.eq(channel))
+            .filter(item_todo_id.eq(todo))
+            .filter(id.eq(item as i32))
+            .returning(item_text)
+            .get_result::<String>(conn)?;
+        log_event(
+            conn,
+            ctx,
+            channel,
+            todo,
+            TodoAction::RemovedChecklistItem,
+            Some(&removed),
+            None,
+        )?;
+        Ok(removed)
+    });
+
+    let data = match removed {
+        Ok(text) => {
+            let header = MessageBuilder::new()
+                .push(format!("Item [{item}] ("))
+                .push_mono_safe(&text)
+                .push(format!(") removed from TODO [{todo_id}]."))
+                .build();
+            format_checklist(ctx, todo, header)
+        }
         Err(NotFound) => "Not found.".to_string(),
-        Err(_) => "Assigning TODO failed.".to_string(),
+        Err(_) => "Removing checklist item failed.".to_string(),
     };
 
     respond_text(ctx, data, true).await;
@@ -458,69 +3165,181 @@ pub async fn assign(
     Ok(())
 }
 
-/// Move TODO entry
-#[poise::command(slash_command, rename = "move")]
-pub async fn rmove(
-    ctx: Context<'_>,
-    #[description = "TODO id"] todo_id: i64,
-    #[description = "TODO new channel"] new_channel: GuildChannel,
-) -> Result<()> {
-    use crate::schema::todos::dsl::{channel_id, id, todo, todos};
+/// Completes the TODO if all its checklist items are checked and it's enabled
+/// in the settings, returns whether it was completed.
+async fn complete_with_checklist(ctx: Context<'_>, todo: i32) -> bool {
+    use crate::schema::{
+        todo_checklist_items::dsl::{
+            channel_id as item_channel_id, checked, todo_checklist_items, todo_id,
+        },
+        todos::dsl::{channel_id, completion_date, deletion_date, id, todos},
+    };
 
-    let new_channel_id = new_channel.id.0 as i64;
-    let new_id = ctx.data().todo_data.get_id(new_channel.id);
+    let settings = ctx
+        .data()
+        .settings
+        .get_bot_settings(ctx, &ctx.channel_id())
+        .await;
+    if !settings.todos.complete_with_checklist {
+        return false;
+    }
+
+    let channel = i64::from(ctx.channel_id());
+    let actor = ctx.author().id;
+
+    let completed = ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+        let unchecked = todo_checklist_items
+            .filter(item_channel_id.eq(channel))
+            .filter(todo_id.eq(todo))
+            .filter(checked.eq(false))
+            .count()
+            .get_result::<i64>(conn)?;
+        // Blocked TODOs have to be completed explicitly
+        let blocked = dependency::get_blocked(conn, channel, &[todo])?;
+        let open = todos
+            .filter(channel_id.eq(channel))
+            .filter(id.eq(todo))
+            .filter(deletion_date.is_null())
+            .filter(completion_date.is_null())
+            .count()
+            .get_result::<i64>(conn)?;
+        if unchecked != 0 || !blocked.is_empty() || open == 0 {
+            return Ok(false);
+        }
+
+        complete_todo(conn, actor, channel, todo)?;
+        QueryResult::Ok(true)
+    });
+
+    completed == Ok(true)
+}
 
-    let moved: QueryResult<String> = diesel::update(todos)
+/// Appends checklist of the TODO to the `header`.
+fn format_checklist(ctx: Context<'_>, todo: i32, header: String) -> String {
+    use crate::schema::todo_checklist_items::dsl::{
+        channel_id, checked, id, text, todo_checklist_items, todo_id,
+    };
+
+    let items = todo_checklist_items
         .filter(channel_id.eq(i64::from(ctx.channel_id())))
-        .filter(id.eq(todo_id as i32))
-        .set((channel_id.eq(new_channel_id), id.eq(new_id)))
-        .returning(todo)
-        .get_result(&mut ctx.data().db.get().unwrap());
+        .filter(todo_id.eq(todo))
+        .order(id.asc())
+        .select((id, text, checked))
+        .load::<ChecklistItem>(&mut ctx.data().db.get().unwrap())
+        .unwrap_or_default();
 
-    let data = match moved {
-        Ok(moved) => MessageBuilder::new()
-            .push(format!("TODO [{}] (", &todo_id))
-            .push_mono_safe(&moved)
-            .push(format!(") moved to {}.", new_channel.name()))
-            .build(),
-        Err(NotFound) => "Not found.".to_string(),
-        Err(_) => "Moving TODO failed.".to_string(),
+    let mut msg = MessageBuilder::new();
+    msg.push_line(header);
+
+    for item in items {
+        let mark = if item.checked { "x" } else { " " };
+        msg.push(format!("`[{mark}]` {}: ", item.id))
+            .push_line_safe(&item.text);
+    }
+
+    msg.build()
+}
+
+fn normalize_tag(tag: &str) -> String {
+    tag.trim()
+        .trim_start_matches('#')
+        .to_lowercase()
+        .split_whitespace()
+        .join("-")
+}
+
+async fn autocomplete_tag(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let Some(guild) = ctx.guild_id() else {
+        return vec![];
     };
 
-    respond_text(ctx, data, false).await;
+    let partial = normalize_tag(partial);
+
+    ctx.data()
+        .todo_data
+        .get_tags(&guild)
+        .await
+        .into_iter()
+        .filter(|t| t.starts_with(&partial))
+        .sorted()
+        .collect()
+}
 
+/// Manage TODO tags
+#[allow(clippy::unused_async)]
+#[poise::command(slash_command, guild_only, subcommands("tag_add", "tag_remove"))]
+pub async fn tag(_ctx: Context<'_>) -> Result<()> {
     Ok(())
 }
 
-/// Edit TODO entry
-#[poise::command(slash_command)]
-pub async fn edit(
+/// Tag TODO entry
+#[poise::command(slash_command, guild_only, rename = "add")]
+pub async fn tag_add(
     ctx: Context<'_>,
     #[description = "TODO id"] todo_id: i64,
-    #[description = "TODO new content"] content: String,
+    #[description = "Tag"]
+    #[autocomplete = "autocomplete_tag"]
+    #[max_length = 32]
+    tag: String,
 ) -> Result<()> {
-    use crate::schema::todos::dsl::{channel_id, id, todo, todos};
+    use crate::schema::{
+        todo_tags::dsl::todo_tags,
+        todos::dsl::{channel_id, deletion_date, id, todo, todos},
+    };
 
-    let data = if content.len() > 1024 {
-        "Content can't have more than 1024 characters.".to_string()
+    let tag = normalize_tag(&tag);
+    let guild = ctx.guild_id().unwrap();
+    let channel = i64::from(ctx.channel_id());
+
+    let data = if tag.is_empty() {
+        "Tag can't be empty.".to_string()
     } else {
-        let text = content.replace('@', "@\u{200B}").replace('`', "'");
+        let conn = &mut ctx.data().db.get().unwrap();
+        let result: QueryResult<String> = conn.immediate_transaction(|conn| {
+            let text = todos
+                .filter(channel_id.eq(channel))
+                .filter(id.eq(todo_id as i32))
+                .filter(deletion_date.is_null())
+                .select(todo)
+                .first::<String>(conn)?;
 
-        let edited: QueryResult<String> = diesel::update(todos)
-            .filter(channel_id.eq(i64::from(ctx.channel_id())))
-            .filter(id.eq(todo_id as i32))
-            .set(todo.eq(text))
-            .returning(todo)
-            .get_result(&mut ctx.data().db.get().unwrap());
+            let new_tag = NewTag {
+                channel_id: &channel,
+                todo_id: &(todo_id as i32),
+                guild_id: &(guild.0 as i64),
+                tag: &tag,
+            };
 
-        match edited {
-            Ok(edited) => MessageBuilder::new()
-                .push(format!("TODO [{}] edited to (", &todo_id))
-                .push_mono_safe(&edited)
-                .push(").".to_string())
-                .build(),
+            let inserted = diesel::insert_or_ignore_into(todo_tags)
+                .values(&new_tag)
+                .execute(conn)?;
+
+            if inserted > 0 {
+                log_event(
+                    conn,
+                    ctx,
+                    channel,
+                    todo_id as i32,
+                    TodoAction::Tagged,
+                    None,
+                    Some(&tag),
+                )?;
+            }
+
+            Ok(text)
+        });
+
+        match result {
+            Ok(text) => {
+                ctx.data().todo_data.add_tag(guild, tag.clone()).await;
+                MessageBuilder::new()
+                    .push(format!("TODO [{todo_id}] ("))
+                    .push_mono_safe(&text)
+                    .push(format!(") tagged with #{tag}."))
+                    .build()
+            }
             Err(NotFound) => "Not found.".to_string(),
-            Err(_) => "Adding TODO failed.".to_string(),
+            Err(_) => "Tagging TODO failed.".to_string(),
         }
     };
 
@@ -529,34 +3348,59 @@ pub async fn edit(
     Ok(())
 }
 
-/// Change priority of TODO entry
-#[poise::command(slash_command)]
-pub async fn set_priority(
+/// Remove tag from TODO entry
+#[poise::command(slash_command, guild_only, rename = "remove")]
+pub async fn tag_remove(
     ctx: Context<'_>,
     #[description = "TODO id"] todo_id: i64,
-    #[description = "TODO new priority"] new_priority: Option<Priority>,
+    #[description = "Tag"]
+    #[autocomplete = "autocomplete_tag"]
+    tag: String,
 ) -> Result<()> {
-    use crate::schema::todos::dsl::{channel_id, id, priority, todo, todos};
+    use crate::schema::todo_tags::dsl::{
+        channel_id, guild_id, tag as tag_column, todo_id as tag_todo_id, todo_tags,
+    };
 
-    let new_priority = new_priority.unwrap_or_default();
-    let new_priority_int = new_priority as i32;
+    let tag = normalize_tag(&tag);
+    let guild = ctx.guild_id().unwrap();
+    let channel = i64::from(ctx.channel_id());
+    let conn = &mut ctx.data().db.get().unwrap();
 
-    let reassigned: QueryResult<String> = diesel::update(todos)
-        .filter(channel_id.eq(i64::from(ctx.channel_id())))
-        .filter(id.eq(todo_id as i32))
-        .set(priority.eq(new_priority_int))
-        .returning(todo)
-        .get_result(&mut ctx.data().db.get().unwrap());
+    let removed = conn.immediate_transaction(|conn| {
+        let removed = diesel::delete(todo_tags)
+            .filter(channel_id.eq(channel))
+            .filter(tag_todo_id.eq(todo_id as i32))
+            .filter(tag_column.eq(&tag))
+            .execute(conn)?;
+        if removed > 0 {
+            log_event(
+                conn,
+                ctx,
+                channel,
+                todo_id as i32,
+                TodoAction::Untagged,
+                Some(&tag),
+                None,
+            )?;
+        }
+        QueryResult::Ok(removed)
+    });
 
-    let data = match reassigned {
-        Ok(reassigned) => MessageBuilder::new()
-            .push(format!("TODO [{}] (", &todo_id))
-            .push_mono_safe(&reassigned)
-            .push(format!(") new priority is {new_priority}."))
-            .build(),
+    let remaining = todo_tags
+        .filter(guild_id.eq(guild.0 as i64))
+        .filter(tag_column.eq(&tag))
+        .count()
+        .get_result::<i64>(conn);
 
-        Err(NotFound) => "Not found.".to_string(),
-        Err(_) => "Assigning TODO failed.".to_string(),
+    let data = match removed {
+        Ok(0) => "Not found.".to_string(),
+        Ok(_) => {
+            if remaining == Ok(0) {
+                ctx.data().todo_data.remove_tag(guild, &tag).await;
+            }
+            format!("Tag #{tag} removed from TODO [{todo_id}].")
+        }
+        Err(_) => "Removing tag failed.".to_string(),
     };
 
     respond_text(ctx, data, true).await;
@@ -586,39 +3430,21 @@ async fn respond_text(ctx: Context<'_>, text: String, ephemeral: bool) {
 #[allow(clippy::too_many_lines)]
 async fn respond_fields(ctx: Context<'_>, fields: Vec<TodoEntry>, query_data: QueryData) {
     let ctx_id = ctx.id();
-    let prev_button_id = format!("{ctx_id}prev");
-    let next_button_id = format!("{ctx_id}next");
-    let refresh_button_id = format!("{ctx_id}refresh");
+    let ctx_prefix = ctx_id.to_string();
 
     let title = get_title(&query_data);
     let mut fields = fields;
     let mut page = 0;
     let mut pages = fields.len().div_ceil(DISCORD_EMBED_FIELDS_LIMIT as usize) as u32;
+    let mut selected: Option<(ChannelId, i32)> = None;
 
     let response = ctx
         .send(|reply| {
-            reply.embed(|embed| {
-                let footer = get_footer(&fields, page, pages);
-                let fields = get_embed_data(&fields, page);
-                embed
-                    .title(&title)
-                    .fields(fields)
-                    .footer(|f| f.text(footer))
-            });
-
-            reply.components(|comp| {
-                comp.create_action_row(|ar| {
-                    ar.create_button(|cb| cb.custom_id(&prev_button_id).emoji('◀'))
-                        .create_button(|cb| {
-                            cb.custom_id(&refresh_button_id)
-                                .label("Refresh")
-                                .style(ButtonStyle::Secondary)
-                        })
-                        .create_button(|cb| cb.custom_id(&next_button_id).emoji('▶'))
-                })
-            });
-
             reply
+                .embed(|embed| create_list_embed(embed, &title, &fields, page, pages, &query_data))
+                .components(|comp| {
+                    create_list_components(comp, ctx_id, &fields, page, None, &query_data)
+                })
         })
         .await;
 
@@ -630,30 +3456,168 @@ async fn respond_fields(ctx: Context<'_>, fields: Vec<TodoEntry>, query_data: Qu
         }
     };
 
-    while let Some(button) =
+    while let Some(interaction) =
         poise::serenity_prelude::CollectComponentInteraction::new(ctx.serenity_context())
             .timeout(Duration::from_secs(60 * 30))
             .message_id(message.id)
             .filter(move |comp| comp.data.custom_id.starts_with(&ctx_id.to_string()))
             .await
     {
-        let interaction_id = button.data.custom_id.clone();
-        if interaction_id == prev_button_id {
-            page = page.checked_sub(1).unwrap_or(pages - 1);
-        } else if interaction_id == next_button_id {
-            page += 1;
-            if page >= pages {
+        let action = interaction
+            .data
+            .custom_id
+            .strip_prefix(&ctx_prefix)
+            .unwrap_or_default();
+        let actor = interaction.user.id;
+        let entry = selected.and_then(|(channel, todo)| {
+            fields
+                .iter()
+                .find(|entry| entry.channel_id == channel && entry.id == todo)
+        });
+
+        if let Some(entry) = entry {
+            let changes = matches!(
+                action,
+                "edit" | "complete" | "uncomplete" | "assign" | "priority"
+            );
+            if changes && !can_change(ctx, &interaction, &query_data, entry).await {
+                let text = "You can't view the channel of this TODO.".to_string();
+                respond_interaction_text(ctx, &interaction, text).await;
+                continue;
+            }
+        }
+
+        let refresh = match (action, entry) {
+            ("prev", _) => {
+                page = page.checked_sub(1).unwrap_or(pages - 1);
+                false
+            }
+            ("next", _) => {
+                page += 1;
+                if page >= pages {
+                    page = 0;
+                }
+                false
+            }
+            ("refresh", _) => {
                 page = 0;
+                true
+            }
+            ("select", _) => {
+                selected = interaction
+                    .data
+                    .values
+                    .first()
+                    .and_then(|value| parse_selection(value));
+                false
+            }
+            ("edit", Some(entry)) => {
+                let edited = edit_from_modal(ctx, interaction.clone(), entry).await;
+                if edited {
+                    if let Err(e) = update_boards(ctx.data(), ctx.http()).await {
+                        debug!("{:?}", e);
+                    }
+                    refresh_list(
+                        ctx,
+                        &mut message,
+                        &title,
+                        &query_data,
+                        &mut fields,
+                        page,
+                        selected,
+                    )
+                    .await;
+                    pages = fields.len().div_ceil(DISCORD_EMBED_FIELDS_LIMIT as usize) as u32;
+                    page = page.min(pages.saturating_sub(1));
+                }
+                continue;
+            }
+            ("complete" | "uncomplete" | "assign" | "priority", Some(entry)) => {
+                let channel = i64::from(entry.channel_id);
+                let todo = entry.id;
+                let new_priority = interaction
+                    .data
+                    .values
+                    .first()
+                    .and_then(|value| value.parse::<i32>().ok())
+                    .filter(|value| (0..Priority::VALUES.len() as i32).contains(value))
+                    .map_or_else(Priority::default, Priority::from);
+                // Whether the TODO was changed, blocked TODOs aren't completed
+                let changed =
+                    ctx.data()
+                        .db
+                        .get()
+                        .unwrap()
+                        .immediate_transaction(|conn| match action {
+                            "complete" => {
+                                if !dependency::get_blocked(conn, channel, &[todo])?.is_empty() {
+                                    return Ok(false);
+                                }
+                                complete_todo(conn, actor, channel, todo).map(|_| true)
+                            }
+                            "uncomplete" => {
+                                uncomplete_todo(conn, actor, channel, todo).map(|_| true)
+                            }
+                            "assign" => {
+                                let mut assignees = get_todo_assignees(conn, channel, todo)?;
+                                if !assignees.contains(&Assignee::Member(actor)) {
+                                    assignees.push(Assignee::Member(actor));
+                                }
+                                assign_todo(conn, actor, channel, todo, &assignees).map(|_| true)
+                            }
+                            _ => set_todo_priority(conn, actor, channel, todo, new_priority)
+                                .map(|_| true),
+                        });
+
+                match changed {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        let text = format!(
+                            "TODO [{todo}] is blocked by uncompleted TODOs, use `/todo complete` \
+                             with `force` to complete it anyway."
+                        );
+                        respond_interaction_text(ctx, &interaction, text).await;
+                        continue;
+                    }
+                    Err(e) => {
+                        let text = match e {
+                            NotFound => "Not found.",
+                            _ => "Changing TODO failed.",
+                        };
+                        respond_interaction_text(ctx, &interaction, text.to_string()).await;
+                        continue;
+                    }
+                }
+
+                if let "complete" | "uncomplete" = action {
+                    let completed = action == "complete";
+                    after_completed(ctx.data(), ctx.http(), &[(channel, todo)], completed).await;
+                }
+                if let Err(e) = update_boards(ctx.data(), ctx.http()).await {
+                    debug!("{:?}", e);
+                }
+                true
             }
-        } else if interaction_id == refresh_button_id {
-            let data = get_todos(ctx, &query_data).await;
-            match data {
+            _ => continue,
+        };
+
+        if refresh {
+            match get_todos(ctx, &query_data).await {
                 EmbedData::Text(text) => {
-                    let response = button
+                    let response = interaction
                         .create_interaction_response(ctx, |ir| {
                             ir.kind(poise::serenity_prelude::InteractionResponseType::UpdateMessage)
                                 .interaction_response_data(|ird| {
-                                    ird.embed(|ce| ce.description(text))
+                                    ird.embed(|ce| ce.description(text)).components(|comp| {
+                                        create_list_components(
+                                            comp,
+                                            ctx_id,
+                                            &[],
+                                            0,
+                                            None,
+                                            &query_data,
+                                        )
+                                    })
                                 })
                         })
                         .await;
@@ -667,21 +3631,27 @@ async fn respond_fields(ctx: Context<'_>, fields: Vec<TodoEntry>, query_data: Qu
                 EmbedData::Fields(embed_fields) => {
                     fields = embed_fields;
                     pages = fields.len().div_ceil(DISCORD_EMBED_FIELDS_LIMIT as usize) as u32;
-                    page = 0;
+                    page = page.min(pages.saturating_sub(1));
                 }
             }
-        } else {
-            continue;
         }
 
-        let footer = get_footer(&fields, page, pages);
-        let fields = get_embed_data(&fields, page);
+        let entry = selected.and_then(|(channel, todo)| {
+            fields
+                .iter()
+                .find(|entry| entry.channel_id == channel && entry.id == todo)
+        });
 
-        let response = button
+        let response = interaction
             .create_interaction_response(ctx, |ir| {
                 ir.kind(poise::serenity_prelude::InteractionResponseType::UpdateMessage)
                     .interaction_response_data(|ird| {
-                        ird.embed(|ce| ce.title(&title).fields(fields).footer(|f| f.text(footer)))
+                        ird.embed(|ce| {
+                            create_list_embed(ce, &title, &fields, page, pages, &query_data)
+                        })
+                        .components(|comp| {
+                            create_list_components(comp, ctx_id, &fields, page, entry, &query_data)
+                        })
                     })
             })
             .await;
@@ -692,12 +3662,10 @@ async fn respond_fields(ctx: Context<'_>, fields: Vec<TodoEntry>, query_data: Qu
     }
 
     page = 0;
-    let footer = get_footer(&fields, page, pages);
-    let fields = get_embed_data(&fields, page);
 
     let response = message
         .edit(ctx, |em| {
-            em.embed(|ce| ce.title(title).fields(fields).footer(|f| f.text(footer)))
+            em.embed(|ce| create_list_embed(ce, &title, &fields, page, pages, &query_data))
                 .components(|cc| cc)
         })
         .await;
@@ -707,7 +3675,316 @@ async fn respond_fields(ctx: Context<'_>, fields: Vec<TodoEntry>, query_data: Qu
     }
 }
 
-fn get_embed_data(fields: &[TodoEntry], page: u32) -> Vec<(String, String, bool)> {
+fn create_list_embed<'a>(
+    embed: &'a mut CreateEmbed,
+    title: &str,
+    fields: &[TodoEntry],
+    page: u32,
+    pages: u32,
+    query_data: &QueryData,
+) -> &'a mut CreateEmbed {
+    let footer = get_footer(fields, page, pages);
+    let fields = get_embed_data(fields, page, query_data.scope);
+    embed.title(title).fields(fields).footer(|f| f.text(footer))
+}
+
+/// Creates page buttons and, unless deleted TODOs are listed, controls to
+/// change the TODO `selected` from the current page.
+fn create_list_components<'a>(
+    comp: &'a mut CreateComponents,
+    ctx_id: u64,
+    fields: &[TodoEntry],
+    page: u32,
+    selected: Option<&TodoEntry>,
+    query_data: &QueryData,
+) -> &'a mut CreateComponents {
+    comp.create_action_row(|ar| {
+        ar.create_button(|cb| cb.custom_id(format!("{ctx_id}prev")).emoji('◀'))
+            .create_button(|cb| {
+                cb.custom_id(format!("{ctx_id}refresh"))
+                    .label("Refresh")
+                    .style(ButtonStyle::Secondary)
+            })
+            .create_button(|cb| cb.custom_id(format!("{ctx_id}next")).emoji('▶'))
+    });
+
+    let skip = (page * DISCORD_EMBED_FIELDS_LIMIT) as usize;
+    if query_data.deleted || fields.len() <= skip {
+        return comp;
+    }
+    let entries = fields
+        .iter()
+        .skip(skip)
+        .take(DISCORD_EMBED_FIELDS_LIMIT as usize);
+
+    comp.create_action_row(|ar| {
+        ar.create_select_menu(|sm| {
+            sm.custom_id(format!("{ctx_id}select"))
+                .placeholder("Select TODO")
+                .options(|opts| {
+                    for entry in entries {
+                        let label: String = format!("[{}] {}", entry.id, entry.text)
+                            .chars()
+                            .take(DISCORD_SELECT_OPTION_LABEL_LIMIT)
+                            .collect();
+                        let is_selected = selected.is_some_and(|s| s == entry);
+                        opts.create_option(|opt| {
+                            opt.label(label)
+                                .value(format!("{}:{}", entry.channel_id, entry.id))
+                                .default_selection(is_selected)
+                        });
+                    }
+                    opts
+                })
+        })
+    })
+    .create_action_row(|ar| {
+        ar.create_select_menu(|sm| {
+            sm.custom_id(format!("{ctx_id}priority"))
+                .placeholder("Set priority")
+                .disabled(selected.is_none())
+                .options(|opts| {
+                    for (value, name) in Priority::VALUES.iter().enumerate() {
+                        let is_current = selected.is_some_and(|s| s.priority == value as i32);
+                        opts.create_option(|opt| {
+                            opt.label(*name).value(value).default_selection(is_current)
+                        });
+                    }
+                    opts
+                })
+        })
+    })
+    .create_action_row(|ar| {
+        let (complete_id, complete_label) = if selected.is_some_and(|s| s.completed) {
+            ("uncomplete", "Uncomplete")
+        } else {
+            ("complete", "Complete")
+        };
+        ar.create_button(|cb| {
+            cb.custom_id(format!("{ctx_id}{complete_id}"))
+                .label(complete_label)
+                .style(ButtonStyle::Success)
+                .disabled(selected.is_none())
+        })
+        .create_button(|cb| {
+            cb.custom_id(format!("{ctx_id}assign"))
+                .label("Assign to me")
+                .style(ButtonStyle::Primary)
+                .disabled(selected.is_none())
+        })
+        .create_button(|cb| {
+            cb.custom_id(format!("{ctx_id}edit"))
+                .label("Edit")
+                .style(ButtonStyle::Secondary)
+                .disabled(selected.is_none())
+        })
+    })
+}
+
+/// Returns whether the member using the list buttons can change the TODO,
+/// server-wide lists are filtered only by permissions of their invoker.
+async fn can_change(
+    ctx: Context<'_>,
+    interaction: &MessageComponentInteraction,
+    query_data: &QueryData,
+    entry: &TodoEntry,
+) -> bool {
+    if query_data.scope == Scope::Channel || interaction.user.id == ctx.author().id {
+        return true;
+    }
+    let Some(member) = &interaction.member else {
+        return false;
+    };
+
+    get_visible_channels(ctx, member)
+        .await
+        .is_some_and(|channels| channels.contains(&i64::from(entry.channel_id)))
+}
+
+/// Parses value of the TODO select menu option.
+fn parse_selection(value: &str) -> Option<(ChannelId, i32)> {
+    let (channel, todo) = value.split_once(':')?;
+    Some((ChannelId(channel.parse().ok()?), todo.parse().ok()?))
+}
+
+#[derive(Debug, poise::Modal)]
+#[name = "Edit TODO"]
+struct TodoEditModal {
+    #[name = "Content"]
+    #[paragraph]
+    #[max_length = 1024]
+    content: String,
+    #[name = "Due date"]
+    #[placeholder = "e.g. 2024-05-01 14:00 or in 3 days, empty removes it"]
+    due: Option<String>,
+    #[name = "Recurrence"]
+    #[placeholder = "e.g. weekly or 0 9 * * mon, empty removes it"]
+    recurrence: Option<String>,
+}
+
+/// Opens modal prefilled with the TODO and applies fields changed in it,
+/// returns whether the TODO was edited.
+async fn edit_from_modal(
+    ctx: Context<'_>,
+    interaction: Arc<MessageComponentInteraction>,
+    entry: &TodoEntry,
+) -> bool {
+    // Text is escaped again once submitted
+    let old_content = unescape_text(&entry.text);
+    let defaults = TodoEditModal {
+        content: old_content.clone(),
+        due: entry.due_date.map(|due| due.format(&TIME_FORMAT).unwrap()),
+        recurrence: entry.recurrence.clone(),
+    };
+    let (old_due, old_recurrence) = (defaults.due.clone(), defaults.recurrence.clone());
+
+    let submitted = poise::execute_modal_on_component_interaction(
+        ctx,
+        interaction.clone(),
+        Some(defaults),
+        Some(MODAL_TIMEOUT),
+    )
+    .await;
+
+    let submitted = match submitted {
+        Ok(Some(submitted)) => submitted,
+        Ok(None) => return false,
+        Err(e) => {
+            debug!("{:?}", e);
+            return false;
+        }
+    };
+
+    // Only changed fields are applied, so that unchanged ones don't have to
+    // be parsed again
+    let content = Some(submitted.content).filter(|content| *content != old_content);
+    let new_due_date = Some(submitted.due)
+        .filter(|due| *due != old_due)
+        .map(|due| due.as_deref().map(parse_due_date));
+    let new_recurrence = Some(submitted.recurrence)
+        .filter(|recurrence| *recurrence != old_recurrence)
+        .map(|recurrence| {
+            recurrence
+                .as_deref()
+                .map(|r| r.parse::<Recurrence>().map(|r| r.to_string()))
+                .transpose()
+        });
+
+    let error = if let Some(Some(None)) = new_due_date {
+        Some("Invalid due date.".to_string())
+    } else if let Some(Err(e)) = &new_recurrence {
+        Some(format!("Invalid recurrence, {e}."))
+    } else if content.is_none() && new_due_date.is_none() && new_recurrence.is_none() {
+        return false;
+    } else {
+        let edited = edit_todo(
+            &mut ctx.data().db.get().unwrap(),
+            interaction.user.id,
+            i64::from(entry.channel_id),
+            entry.id,
+            content,
+            new_due_date.map(Option::flatten),
+            new_recurrence.and_then(std::result::Result::ok),
+        );
+
+        match edited {
+            Ok(_) => None,
+            Err(NotFound) => Some("Not found.".to_string()),
+            Err(_) => Some("Editing TODO failed.".to_string()),
+        }
+    };
+
+    let Some(error) = error else {
+        return true;
+    };
+
+    let response = interaction
+        .create_followup_message(ctx, |f| {
+            f.embed(|embed| embed.description(error)).ephemeral(true)
+        })
+        .await;
+
+    if let Err(e) = response {
+        debug!("{:?}", e);
+    }
+
+    false
+}
+
+/// Reloads TODOs of the list and edits its message, used when the list
+/// interaction was already responded to.
+async fn refresh_list(
+    ctx: Context<'_>,
+    message: &mut Message,
+    title: &str,
+    query_data: &QueryData,
+    fields: &mut Vec<TodoEntry>,
+    page: u32,
+    selected: Option<(ChannelId, i32)>,
+) {
+    let response = match get_todos(ctx, query_data).await {
+        EmbedData::Text(text) => {
+            message
+                .edit(ctx, |em| {
+                    em.embed(|ce| ce.description(text)).components(|comp| {
+                        create_list_components(comp, ctx.id(), &[], 0, None, query_data)
+                    })
+                })
+                .await
+        }
+        EmbedData::Fields(embed_fields) => {
+            *fields = embed_fields;
+            let pages = fields.len().div_ceil(DISCORD_EMBED_FIELDS_LIMIT as usize) as u32;
+            let page = page.min(pages.saturating_sub(1));
+            let selected = selected.and_then(|(channel, todo)| {
+                fields
+                    .iter()
+                    .find(|entry| entry.channel_id == channel && entry.id == todo)
+            });
+            message
+                .edit(ctx, |em| {
+                    em.embed(|ce| create_list_embed(ce, title, fields, page, pages, query_data))
+                        .components(|comp| {
+                            create_list_components(
+                                comp,
+                                ctx.id(),
+                                fields,
+                                page,
+                                selected,
+                                query_data,
+                            )
+                        })
+                })
+                .await
+        }
+    };
+
+    if let Err(e) = response {
+        debug!("{:?}", e);
+    }
+}
+
+async fn respond_interaction_text(
+    ctx: Context<'_>,
+    interaction: &MessageComponentInteraction,
+    text: String,
+) {
+    let response = interaction
+        .create_interaction_response(ctx, |ir| {
+            ir.kind(poise::serenity_prelude::InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|ird| {
+                    ird.embed(|ce| ce.description(text)).ephemeral(true)
+                })
+        })
+        .await;
+
+    if let Err(e) = response {
+        debug!("{:?}", e);
+    }
+}
+
+fn get_embed_data(fields: &[TodoEntry], page: u32, scope: Scope) -> Vec<(String, String, bool)> {
+    let now = OffsetDateTime::now_utc();
     let skip = page * DISCORD_EMBED_FIELDS_LIMIT;
     let new_fields: Vec<(String, String, bool)> = fields
         .iter()
@@ -718,33 +3995,114 @@ fn get_embed_data(fields: &[TodoEntry], page: u32) -> Vec<(String, String, bool)
                 let priority = Priority::from(entry.priority);
                 title = format!("{title} {priority}");
             }
+            if let Some(checklist) = entry.checklist {
+                title = format!("{title} {}/{}", checklist.checked, checklist.total);
+            }
+            if let Some(due) = entry.due_date {
+                let due = due.format(&DUE_DATE_FORMAT).unwrap();
+                title = format!("{title} (due {due})");
+            }
             if entry.completed {
                 title = format!("{title} [DONE]");
+            } else if entry.is_overdue(now) {
+                title = format!("{title} [OVERDUE]");
             }
-            if let Some(nick) = &entry.assignee {
-                title = format!("{title} - {nick}");
+            if !entry.assignees.is_empty() {
+                title = format!("{title} - {}", entry.assignees.join(", "));
             };
-            (title, entry.text.clone(), false)
+
+            let mut details = vec![];
+            if scope == Scope::Guild {
+                details.push(format!("in {}", entry.channel_id.mention()));
+            }
+            if let Some(recurrence) = &entry.recurrence {
+                details.push(format!("repeats `{recurrence}`"));
+            }
+            if let Some(issue) = entry.issue {
+                details.push(format!("issue #{issue}"));
+            }
+            if let Some(link) = &entry.message_link {
+                details.push(format!("[source message]({link})"));
+            }
+            if let Some(thread) = entry.thread {
+                details.push(format!("discussed in {}", thread.mention()));
+            }
+            if let Some(deleted) = entry.deletion_date {
+                details.push(format!("deleted <t:{}:R>", deleted.unix_timestamp()));
+            }
+            if !entry.completed && !entry.blocked_by.is_empty() {
+                let channel = i64::from(entry.channel_id);
+                let blockers = entry
+                    .blocked_by
+                    .iter()
+                    .map(|blocker| dependency::format_blocker(*blocker, channel))
+                    .join(", ");
+                details.push(format!("blocked by {blockers}"));
+            }
+            if !entry.tags.is_empty() {
+                details.push(entry.tags.iter().map(|t| format!("`#{t}`")).join(" "));
+            }
+
+            (title, get_field_value(&entry.text, &details), false)
         })
         .take(DISCORD_EMBED_FIELDS_LIMIT as usize)
         .collect();
     new_fields
 }
 
+/// Appends details to the TODO text, shortening the text if the result would
+/// not fit in the embed field.
+fn get_field_value(text: &str, details: &[String]) -> String {
+    if details.is_empty() {
+        return text.to_string();
+    }
+
+    let details = details.join("\n");
+    let available = DISCORD_EMBED_FIELD_VALUE_LIMIT.saturating_sub(details.chars().count() + 2);
+    let text = if text.chars().count() > available {
+        let shortened: String = text.chars().take(available.saturating_sub(1)).collect();
+        format!("{shortened}…")
+    } else {
+        text.to_string()
+    };
+
+    format!("{text}\n{details}")
+}
+
 fn get_footer(fields: &[TodoEntry], page: u32, pages: u32) -> String {
+    let now = OffsetDateTime::now_utc();
     let total = fields.iter().filter(|te| !te.completed).count();
-    let footer = format!("Page {}/{pages}: {total} uncompleted TODOs", page + 1);
+    let overdue = fields.iter().filter(|te| te.is_overdue(now)).count();
+    let mut footer = format!("Page {}/{pages}: {total} uncompleted TODOs", page + 1);
+    if overdue > 0 {
+        footer = format!("{footer}, {overdue} overdue");
+    }
     footer
 }
 
 fn get_title(query_data: &QueryData) -> String {
-    let mut title = String::from("TODOs");
+    let mut title = match query_data.scope {
+        Scope::Channel => String::from("TODOs"),
+        Scope::Guild => String::from("Server TODOs"),
+    };
+
+    if query_data.deleted {
+        title = format!("Deleted {title}");
+    }
 
     if let Some(assignee) = &query_data.todo_assignee {
         title = format!("{title} assigned to {}", assignee.user.name);
     }
 
-    if query_data.completed {
+    if let Some(tag) = &query_data.tag {
+        title = format!("{title} tagged #{tag}");
+    }
+
+    if let Some(search) = &query_data.search {
+        title = format!("{title} matching \"{search}\"");
+    }
+
+    if query_data.completed && !query_data.deleted {
         title = format!("{title} (w/ completed)");
     }
 
@@ -752,5 +4110,27 @@ fn get_title(query_data: &QueryData) -> String {
         title = format!("{title} (sorted by priority)");
     }
 
+    if query_data.sort_by_due {
+        title = format!("{title} (sorted by due date)");
+    }
+
     title
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_text_is_idempotent() {
+        let escaped = escape_text("ping @everyone about `code`");
+        assert_eq!(escaped, "ping @\u{200B}everyone about 'code'");
+        assert_eq!(escape_text(&escaped), escaped);
+        assert_eq!(
+            escape_text(&unescape_text(&escaped)),
+            escaped,
+            "editing unchanged prefill"
+        );
+        assert_eq!(unescape_text(&escaped), "ping @everyone about 'code'");
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+
+use diesel::{prelude::*, result::QueryResult};
+use poise::serenity_prelude::{
+    Channel, ChannelId, CreateEmbed, GuildId, Http, HttpError, MessageId, SerenityError, StatusCode,
+};
+use time::OffsetDateTime;
+use tracing::{debug, warn};
+
+use super::{
+    dependency, get_assignees, get_checklist_progress, get_embed_data, get_tags, respond_text,
+    Scope, TodoEntry,
+};
+use crate::{
+    commands::DISCORD_EMBED_FIELDS_LIMIT,
+    ctx_data::CtxData,
+    models::todo::{Board, Todo},
+    Context, Result,
+};
+
+/// Post board with TODOs of the channel and pin it, it's updated whenever
+/// the TODOs change
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "pin-board",
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn pin_board(ctx: Context<'_>) -> Result<()> {
+    use crate::schema::todo_boards::dsl::{channel_id, last_event_id, message_id, todo_boards};
+
+    ctx.defer_ephemeral().await?;
+
+    let channel = ctx.channel_id();
+    let guild = ctx.guild_id().unwrap();
+
+    let latest = get_latest_events(&mut *ctx.data().db.get()?, &[i64::from(channel)])
+        .map(|latest| latest.get(&i64::from(channel)).copied().flatten());
+    let entries = get_board_entries(ctx.data(), ctx.http(), channel, guild).await;
+
+    let (Ok(latest), Ok(entries)) = (latest, entries) else {
+        respond_text(ctx, "Creating board failed.".to_string(), true).await;
+        return Ok(());
+    };
+
+    let message = channel
+        .send_message(ctx, |m| {
+            m.embed(|embed| create_board_embed(embed, &entries))
+        })
+        .await;
+    let message = match message {
+        Ok(message) => message,
+        Err(e) => {
+            debug!("{:?}", e);
+            respond_text(ctx, "Posting board failed.".to_string(), true).await;
+            return Ok(());
+        }
+    };
+
+    let pinned = message.pin(ctx).await;
+
+    let board_channel = i64::from(channel);
+    let board_message = message.id.0 as i64;
+    let previous: QueryResult<Option<i64>> = ctx.data().db.get()?.immediate_transaction(|conn| {
+        let previous = todo_boards
+            .filter(channel_id.eq(board_channel))
+            .select(message_id)
+            .first::<i64>(conn)
+            .optional()?;
+        diesel::insert_into(todo_boards)
+            .values((
+                channel_id.eq(board_channel),
+                message_id.eq(board_message),
+                last_event_id.eq(latest),
+            ))
+            .on_conflict(channel_id)
+            .do_update()
+            .set((message_id.eq(board_message), last_event_id.eq(latest)))
+            .execute(conn)?;
+        Ok(previous)
+    });
+
+    let data = match (previous, pinned) {
+        (Ok(previous), pinned) => {
+            // There is only one board per channel, the old one would get stale
+            if let Some(previous) = previous {
+                let deleted = channel
+                    .delete_message(ctx, MessageId(previous as u64))
+                    .await;
+                if let Err(e) = deleted {
+                    debug!("{:?}", e);
+                }
+            }
+            match pinned {
+                Ok(()) => "Board pinned, it's updated whenever TODOs in the channel change.",
+                Err(e) => {
+                    debug!("{:?}", e);
+                    "Board posted, but pinning it failed."
+                }
+            }
+        }
+        (Err(_), _) => {
+            let deleted = message.delete(ctx).await;
+            if let Err(e) = deleted {
+                debug!("{:?}", e);
+            }
+            "Saving board failed."
+        }
+    };
+
+    respond_text(ctx, data.to_string(), true).await;
+
+    Ok(())
+}
+
+/// Edits boards of channels whose TODOs changed since the last update, boards
+/// with deleted messages are forgotten.
+pub async fn update_boards(ctx_data: &CtxData, http: &Http) -> Result<()> {
+    use crate::schema::todo_boards::dsl::{channel_id, last_event_id, message_id, todo_boards};
+
+    let (boards, latest) = {
+        let conn = &mut ctx_data.db.get()?;
+        let boards = todo_boards.load::<Board>(conn)?;
+        let channels: Vec<i64> = boards.iter().map(|board| board.channel_id).collect();
+        (boards, get_latest_events(conn, &channels)?)
+    };
+
+    for board in boards {
+        let latest = latest.get(&board.channel_id).copied().flatten();
+        if latest == board.last_event_id {
+            continue;
+        }
+
+        let channel = ChannelId(board.channel_id as u64);
+        let Some(guild) = channel
+            .to_channel(http)
+            .await
+            .ok()
+            .and_then(Channel::guild)
+            .map(|c| c.guild_id)
+        else {
+            continue;
+        };
+
+        let entries = match get_board_entries(ctx_data, http, channel, guild).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("Getting TODOs of board in channel {channel} failed: {e:?}");
+                continue;
+            }
+        };
+        let edited = channel
+            .edit_message(http, MessageId(board.message_id as u64), |m| {
+                m.embed(|embed| create_board_embed(embed, &entries))
+            })
+            .await;
+
+        let conn = &mut ctx_data.db.get()?;
+        let board_row = todo_boards
+            .filter(channel_id.eq(board.channel_id))
+            .filter(message_id.eq(board.message_id));
+
+        match edited {
+            Ok(_) => {
+                diesel::update(board_row)
+                    .set(last_event_id.eq(latest))
+                    .execute(conn)?;
+            }
+            Err(e) if is_not_found(&e) => {
+                debug!("Board in channel {channel} was deleted");
+                diesel::delete(board_row).execute(conn)?;
+            }
+            Err(e) => debug!("{:?}", e),
+        }
+    }
+
+    Ok(())
+}
+
+fn is_not_found(error: &SerenityError) -> bool {
+    matches!(
+        error,
+        SerenityError::Http(e)
+            if matches!(e.as_ref(), HttpError::UnsuccessfulRequest(response)
+                if response.status_code == StatusCode::NOT_FOUND)
+    )
+}
+
+/// Returns ID of the latest event in each of the channels, they are compared
+/// instead of timestamps because events of moved TODOs move with them.
+fn get_latest_events(
+    conn: &mut SqliteConnection,
+    channels: &[i64],
+) -> QueryResult<HashMap<i64, Option<i32>>> {
+    use crate::schema::todo_events::dsl::{channel_id, id, todo_events};
+
+    let latest = todo_events
+        .filter(channel_id.eq_any(channels))
+        .group_by(channel_id)
+        .select((channel_id, diesel::dsl::max(id)))
+        .load::<(i64, Option<i32>)>(conn)?;
+
+    Ok(latest.into_iter().collect())
+}
+
+/// Returns uncompleted TODOs of the channel, the most important first.
+async fn get_board_entries(
+    ctx_data: &CtxData,
+    http: &Http,
+    channel: ChannelId,
+    guild: GuildId,
+) -> QueryResult<Vec<TodoEntry>> {
+    use crate::schema::todos::dsl::{channel_id, completion_date, deletion_date, todos};
+
+    let channels = [i64::from(channel)];
+    let (todo_list, assignees, progress, mut tags, mut blockers) = {
+        let conn = &mut ctx_data.db.get().unwrap();
+        let todo_list = todos
+            .filter(channel_id.eq(channels[0]))
+            .filter(completion_date.is_null())
+            .filter(deletion_date.is_null())
+            .load::<Todo>(conn)?;
+        (
+            todo_list,
+            get_assignees(conn, &channels)?,
+            get_checklist_progress(conn, &channels)?,
+            get_tags(conn, &channels)?,
+            dependency::get_open_blockers(conn, &channels)?,
+        )
+    };
+
+    let mut entries = vec![];
+    for todo in todo_list {
+        let key = (todo.channel_id, todo.id);
+        let todo_assignees = assignees.get(&key).map_or(&[][..], Vec::as_slice);
+        let mut entry = TodoEntry::in_guild(todo, todo_assignees, http, Some(guild)).await;
+        entry.checklist = progress.get(&key).copied();
+        entry.tags = tags.remove(&key).unwrap_or_default();
+        entry.blocked_by = blockers.remove(&key).unwrap_or_default();
+        entries.push(entry);
+    }
+
+    entries.sort_by_key(|entry| {
+        (
+            -entry.priority,
+            entry.due_date.is_none(),
+            entry.due_date,
+            entry.id,
+        )
+    });
+
+    Ok(entries)
+}
+
+fn create_board_embed<'a>(
+    embed: &'a mut CreateEmbed,
+    entries: &[TodoEntry],
+) -> &'a mut CreateEmbed {
+    let now = OffsetDateTime::now_utc();
+    let overdue = entries.iter().filter(|entry| entry.is_overdue(now)).count();
+
+    let mut footer = format!("{} uncompleted TODOs", entries.len());
+    if overdue > 0 {
+        footer = format!("{footer}, {overdue} overdue");
+    }
+    if entries.len() > DISCORD_EMBED_FIELDS_LIMIT as usize {
+        footer = format!("{footer}, the first {DISCORD_EMBED_FIELDS_LIMIT} shown");
+    }
+
+    embed.title("TODO board");
+    if entries.is_empty() {
+        embed.description("There are no incompleted TODOs in this channel.");
+    } else {
+        embed.fields(get_embed_data(entries, 0, Scope::Channel));
+    }
+
+    embed
+        .footer(|f| f.text(footer))
+        .timestamp(poise::serenity_prelude::Timestamp::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel_migrations::MigrationHarness;
+    use poise::serenity_prelude::UserId;
+
+    use super::*;
+    use crate::{
+        commands::todo::{insert_event, move_todo, next_todo_id, TodoAction},
+        models::todo::NewTodo,
+        MIGRATIONS,
+    };
+
+    fn add_todo(conn: &mut SqliteConnection, channel: i64) -> i32 {
+        use crate::schema::todos::dsl::todos;
+
+        let new_id = next_todo_id(conn, channel).unwrap();
+        let new_todo = NewTodo {
+            channel_id: &channel,
+            id: &new_id,
+            todo: "todo",
+            creation_date: "2024-05-01 12:00:00",
+            completion_date: None,
+            priority: 0,
+            due_date: None,
+            recurrence: None,
+            issue: None,
+            message_link: None,
+        };
+        diesel::insert_into(todos)
+            .values(&new_todo)
+            .execute(conn)
+            .unwrap();
+        insert_event(
+            conn,
+            UserId(1),
+            channel,
+            new_id,
+            TodoAction::Created,
+            None,
+            Some("todo"),
+        )
+        .unwrap();
+        new_id
+    }
+
+    #[test]
+    fn latest_events_change_in_both_channels_on_move() {
+        let conn = &mut SqliteConnection::establish(":memory:").unwrap();
+        conn.run_pending_migrations(MIGRATIONS).unwrap();
+
+        let todo = add_todo(conn, 1);
+        add_todo(conn, 2);
+        let before = get_latest_events(conn, &[1, 2]).unwrap();
+
+        move_todo(conn, UserId(1), 1, todo, 2).unwrap();
+        let after = get_latest_events(conn, &[1, 2]).unwrap();
+
+        assert_ne!(before[&1], after[&1]);
+        assert_ne!(before[&2], after[&2]);
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use diesel::{
+    prelude::*,
+    result::{Error::NotFound, QueryResult},
+};
+use itertools::Itertools;
+use poise::serenity_prelude::{GuildChannel, Http, MessageBuilder, UserId};
+use tracing::debug;
+
+use super::{get_todo_assignees, log_event, respond_text, Assignee, TodoAction};
+use crate::{ctx_data::CtxData, models::todo::Todo, Context, Result};
+
+/// Channel ID and ID of a TODO.
+type TodoKey = (i64, i32);
+
+/// Manage TODOs blocking other TODOs
+#[allow(clippy::unused_async)]
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands("blocker_add", "blocker_remove")
+)]
+pub async fn blocker(_ctx: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// Mark TODO entry as blocked by another TODO
+#[poise::command(slash_command, guild_only, rename = "add")]
+pub async fn blocker_add(
+    ctx: Context<'_>,
+    #[description = "TODO id"] todo_id: i64,
+    #[description = "Blocking TODO id"] blocker_id: i64,
+    #[description = "Channel of the blocking TODO, this channel by default"]
+    blocker_channel: Option<GuildChannel>,
+) -> Result<()> {
+    use crate::schema::{
+        todo_dependencies::dsl::{
+            blocker_channel_id, blocker_id as dependency_blocker_id,
+            channel_id as dependency_channel_id, todo_dependencies, todo_id as dependency_todo_id,
+        },
+        todos::dsl::{channel_id, deletion_date, id, todo, todos},
+    };
+
+    let channel = i64::from(ctx.channel_id());
+    let dependent = (channel, todo_id as i32);
+    let blocking = (
+        blocker_channel.map_or(channel, |c| i64::from(c.id)),
+        blocker_id as i32,
+    );
+    let blocker_text = format_blocker(blocking, channel);
+
+    let added: QueryResult<Option<String>> = ctx.data().db.get()?.immediate_transaction(|conn| {
+        let text = todos
+            .filter(channel_id.eq(dependent.0))
+            .filter(id.eq(dependent.1))
+            .filter(deletion_date.is_null())
+            .select(todo)
+            .first::<String>(conn)?;
+        todos
+            .filter(channel_id.eq(blocking.0))
+            .filter(id.eq(blocking.1))
+            .filter(deletion_date.is_null())
+            .select(id)
+            .first::<i32>(conn)?;
+
+        if blocking == dependent || is_blocked_by(conn, blocking, dependent)? {
+            return Ok(None);
+        }
+
+        let inserted = diesel::insert_or_ignore_into(todo_dependencies)
+            .values((
+                dependency_channel_id.eq(dependent.0),
+                dependency_todo_id.eq(dependent.1),
+                blocker_channel_id.eq(blocking.0),
+                dependency_blocker_id.eq(blocking.1),
+            ))
+            .execute(conn)?;
+        if inserted > 0 {
+            log_event(
+                conn,
+                ctx,
+                channel,
+                dependent.1,
+                TodoAction::AddedBlocker,
+                None,
+                Some(&blocker_text),
+            )?;
+        }
+
+        Ok(Some(text))
+    });
+
+    let data = match added {
+        Ok(Some(text)) => MessageBuilder::new()
+            .push(format!("TODO [{todo_id}] ("))
+            .push_mono_safe(&text)
+            .push(format!(") is blocked by TODO {blocker_text}."))
+            .build(),
+        Ok(None) => "TODO can't be blocked by itself or by TODOs it blocks.".to_string(),
+        Err(NotFound) => "Not found.".to_string(),
+        Err(_) => "Adding blocker failed.".to_string(),
+    };
+
+    respond_text(ctx, data, true).await;
+
+    Ok(())
+}
+
+/// Remove blocking TODO from TODO entry
+#[poise::command(slash_command, guild_only, rename = "remove")]
+pub async fn blocker_remove(
+    ctx: Context<'_>,
+    #[description = "TODO id"] todo_id: i64,
+    #[description = "Blocking TODO id"] blocker_id: i64,
+    #[description = "Channel of the blocking TODO, this channel by default"]
+    blocker_channel: Option<GuildChannel>,
+) -> Result<()> {
+    use crate::schema::{
+        todo_dependencies::dsl::{
+            blocker_channel_id, blocker_id as dependency_blocker_id,
+            channel_id as dependency_channel_id, todo_dependencies, todo_id as dependency_todo_id,
+        },
+        todos::dsl::{channel_id, completion_date, deletion_date, id, todos},
+    };
+
+    let channel = i64::from(ctx.channel_id());
+    let dependent = (channel, todo_id as i32);
+    let blocking = (
+        blocker_channel.map_or(channel, |c| i64::from(c.id)),
+        blocker_id as i32,
+    );
+    let blocker_text = format_blocker(blocking, channel);
+
+    let removed = ctx.data().db.get()?.immediate_transaction(|conn| {
+        let removed = diesel::delete(todo_dependencies)
+            .filter(dependency_channel_id.eq(dependent.0))
+            .filter(dependency_todo_id.eq(dependent.1))
+            .filter(blocker_channel_id.eq(blocking.0))
+            .filter(dependency_blocker_id.eq(blocking.1))
+            .execute(conn)?;
+        if removed == 0 {
+            return Err(NotFound);
+        }
+        log_event(
+            conn,
+            ctx,
+            channel,
+            dependent.1,
+            TodoAction::RemovedBlocker,
+            Some(&blocker_text),
+            None,
+        )?;
+
+        // Removing completed blocker doesn't change anything for the assignee
+        let was_open = todos
+            .filter(channel_id.eq(blocking.0))
+            .filter(id.eq(blocking.1))
+            .filter(completion_date.is_null())
+            .filter(deletion_date.is_null())
+            .count()
+            .get_result::<i64>(conn)?;
+        QueryResult::Ok(was_open > 0)
+    });
+
+    let data = match removed {
+        Ok(was_open) => {
+            if was_open {
+                notify_unblocked(ctx.data(), ctx.http(), &[], &[dependent]).await;
+            }
+            format!("TODO [{todo_id}] is no longer blocked by TODO {blocker_text}.")
+        }
+        Err(NotFound) => "Not found.".to_string(),
+        Err(_) => "Removing blocker failed.".to_string(),
+    };
+
+    respond_text(ctx, data, true).await;
+
+    Ok(())
+}
+
+/// Formats the blocking TODO, its channel is mentioned if it's not `channel`.
+pub(super) fn format_blocker(blocker: TodoKey, channel: i64) -> String {
+    if blocker.0 == channel {
+        format!("[{}]", blocker.1)
+    } else {
+        format!("<#{}> [{}]", blocker.0, blocker.1)
+    }
+}
+
+/// Whether `todo` is blocked by `blocker`, directly or through other TODOs,
+/// regardless of whether they are completed.
+fn is_blocked_by(
+    conn: &mut SqliteConnection,
+    todo: TodoKey,
+    blocker: TodoKey,
+) -> QueryResult<bool> {
+    use crate::schema::todo_dependencies::dsl::{
+        blocker_channel_id, blocker_id, channel_id, todo_dependencies, todo_id,
+    };
+
+    let mut visited = HashSet::from([todo]);
+    let mut queue = VecDeque::from([todo]);
+
+    while let Some((channel, id)) = queue.pop_front() {
+        let blockers = todo_dependencies
+            .filter(channel_id.eq(channel))
+            .filter(todo_id.eq(id))
+            .select((blocker_channel_id, blocker_id))
+            .load::<TodoKey>(conn)?;
+
+        for key in blockers {
+            if key == blocker {
+                return Ok(true);
+            }
+            if visited.insert(key) {
+                queue.push_back(key);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Returns uncompleted blockers of TODOs in the channels, TODOs without them
+/// aren't included.
+pub(super) fn get_open_blockers(
+    conn: &mut SqliteConnection,
+    channels: &[i64],
+) -> QueryResult<HashMap<TodoKey, Vec<TodoKey>>> {
+    use crate::schema::{
+        todo_dependencies::dsl::{
+            blocker_channel_id, blocker_id, channel_id, todo_dependencies, todo_id,
+        },
+        todos,
+    };
+
+    let blockers = todo_dependencies
+        .inner_join(
+            todos::table.on(todos::channel_id
+                .eq(blocker_channel_id)
+                .and(todos::id.eq(blocker_id))),
+        )
+        .filter(channel_id.eq_any(channels))
+        .filter(todos::completion_date.is_null())
+        .filter(todos::deletion_date.is_null())
+        .select((channel_id, todo_id, blocker_channel_id, blocker_id))
+        .order((blocker_channel_id.asc(), blocker_id.asc()))
+        .load::<(i64, i32, i64, i32)>(conn)?
+        .into_iter()
+        .into_group_map_by(|(channel, todo, _, _)| (*channel, *todo))
+        .into_iter()
+        .map(|(key, blockers)| {
+            let blockers = blockers.into_iter().map(|(_, _, c, b)| (c, b)).collect();
+            (key, blockers)
+        })
+        .collect();
+
+    Ok(blockers)
+}
+
+/// Returns IDs of the TODOs in the channel which have uncompleted blockers.
+pub(super) fn get_blocked(
+    conn: &mut SqliteConnection,
+    channel: i64,
+    todo_ids: &[i32],
+) -> QueryResult<Vec<i32>> {
+    let blocked = get_open_blockers(conn, &[channel])?
+        .into_keys()
+        .map(|(_, todo)| todo)
+        .filter(|todo| todo_ids.contains(todo))
+        .sorted()
+        .collect();
+
+    Ok(blocked)
+}
+
+/// Sends direct message to assignees of TODOs which are no longer blocked,
+/// either because `closed` TODOs blocking them were completed or deleted, or
+/// because blockers of `dependents` were removed.
+pub(super) async fn notify_unblocked(
+    ctx_data: &CtxData,
+    http: &Http,
+    closed: &[TodoKey],
+    dependents: &[TodoKey],
+) {
+    let unblocked = match ctx_data.db.get() {
+        Ok(mut conn) => get_unblocked(&mut conn, closed, dependents),
+        Err(e) => {
+            debug!("{:?}", e);
+            return;
+        }
+    };
+
+    let unblocked = match unblocked {
+        Ok(unblocked) => unblocked,
+        Err(e) => {
+            debug!("{:?}", e);
+            return;
+        }
+    };
+
+    for (todo, assignees) in unblocked {
+        let description = MessageBuilder::new()
+            .push(format!("TODO [{}] (", todo.id))
+            .push_mono_safe(&todo.todo)
+            .push(format!(
+                ") in <#{}> is no longer blocked, TODOs blocking it are done.",
+                todo.channel_id
+            ))
+            .build();
+
+        for assignee in assignees {
+            let channel = match assignee.create_dm_channel(http).await {
+                Ok(channel) => channel,
+                Err(e) => {
+                    debug!("Error while creating DM channel: {:?}", e);
+                    continue;
+                }
+            };
+
+            let response = channel
+                .send_message(http, |message| {
+                    message.embed(|embed| {
+                        embed
+                            .title("TODO unblocked")
+                            .description(description.clone())
+                    })
+                })
+                .await;
+
+            if let Err(e) = response {
+                debug!("Error while sending unblocked notification: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Returns uncompleted TODOs assigned to members blocked by `closed` TODOs, or
+/// included in `dependents`, that have no uncompleted blockers left, with the
+/// members.
+fn get_unblocked(
+    conn: &mut SqliteConnection,
+    closed: &[TodoKey],
+    dependents: &[TodoKey],
+) -> QueryResult<Vec<(Todo, Vec<UserId>)>> {
+    use crate::schema::{
+        todo_dependencies::dsl::{
+            blocker_channel_id, blocker_id, channel_id as dependency_channel_id, todo_dependencies,
+            todo_id,
+        },
+        todos::dsl::{channel_id, completion_date, deletion_date, id, todos},
+    };
+
+    let closed_channels: Vec<i64> = closed
+        .iter()
+        .map(|(channel, _)| *channel)
+        .unique()
+        .collect();
+    let candidates: Vec<TodoKey> = todo_dependencies
+        .filter(blocker_channel_id.eq_any(&closed_channels))
+        .select((
+            dependency_channel_id,
+            todo_id,
+            blocker_channel_id,
+            blocker_id,
+        ))
+        .load::<(i64, i32, i64, i32)>(conn)?
+        .into_iter()
+        .filter(|(_, _, c, b)| closed.contains(&(*c, *b)))
+        .map(|(channel, todo, _, _)| (channel, todo))
+        .chain(dependents.iter().copied())
+        .unique()
+        .collect();
+
+    let channels: Vec<i64> = candidates
+        .iter()
+        .map(|(channel, _)| *channel)
+        .unique()
+        .collect();
+    let blocked = get_open_blockers(conn, &channels)?;
+
+    let mut unblocked = vec![];
+    for key in candidates
+        .into_iter()
+        .filter(|key| !blocked.contains_key(key))
+    {
+        // Members of assigned roles aren't known without the privileged members
+        // intent, so only the assigned members are notified
+        let assignees: Vec<UserId> = get_todo_assignees(conn, key.0, key.1)?
+            .into_iter()
+            .filter_map(|assignee| match assignee {
+                Assignee::Member(user) => Some(user),
+                Assignee::Role(_) => None,
+            })
+            .collect();
+        if assignees.is_empty() {
+            continue;
+        }
+        let todo = todos
+            .filter(channel_id.eq(key.0))
+            .filter(id.eq(key.1))
+            .filter(completion_date.is_null())
+            .filter(deletion_date.is_null())
+            .first::<Todo>(conn)
+            .optional()?;
+        unblocked.extend(todo.map(|todo| (todo, assignees)));
+    }
+
+    Ok(unblocked)
+}
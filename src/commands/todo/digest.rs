@@ -0,0 +1,275 @@
+use std::collections::{HashMap, HashSet};
+
+use diesel::{prelude::*, result::QueryResult};
+use itertools::Itertools;
+use poise::serenity_prelude::{ChannelId, CreateEmbed, Http};
+use time::{Duration, OffsetDateTime};
+use tracing::debug;
+
+use super::{get_assignees, Priority, TodoAction};
+use crate::{
+    commands::{parse_time, DISCORD_EMBED_FIELD_VALUE_LIMIT, TIME_FORMAT},
+    ctx_data::CtxData,
+    models::todo::Todo,
+    settings::Feature,
+    Result,
+};
+
+// Period summarized by the digest
+const DIGEST_DAYS: i64 = 7;
+// TODO texts longer than that are shortened in the lists
+const MAX_TEXT_LENGTH: usize = 64;
+
+/// Activity in the channel over the last week and TODOs needing attention.
+struct Digest<'a> {
+    created: usize,
+    completed: usize,
+    // Members who completed TODOs with the number of them, most active first
+    completed_by: Vec<(i64, usize)>,
+    open: usize,
+    overdue: Vec<(&'a Todo, OffsetDateTime)>,
+    unassigned: Vec<&'a Todo>,
+    oldest: Vec<(&'a Todo, OffsetDateTime)>,
+}
+
+impl<'a> Digest<'a> {
+    /// Summarizes TODOs of the channel, `assigned` are keys of TODOs with
+    /// assignees and `completions` TODO IDs and members who completed them in
+    /// the period.
+    fn new(
+        todo_list: &'a [Todo],
+        assigned: &HashSet<(i64, i32)>,
+        completions: &[(i32, i64)],
+        now: OffsetDateTime,
+    ) -> Self {
+        let start = now - Duration::days(DIGEST_DAYS);
+
+        let created = todo_list
+            .iter()
+            .filter(|todo| parse_time(&todo.creation_date).is_some_and(|c| c > start))
+            .count();
+
+        // Only the last completion of each TODO counts, in case it was
+        // uncompleted in between
+        let completers: HashMap<i32, i64> = completions.iter().copied().collect();
+        let completed_by = completers
+            .values()
+            .counts()
+            .into_iter()
+            .map(|(actor, count)| (*actor, count))
+            .sorted_by_key(|(actor, count)| (std::cmp::Reverse(*count), *actor))
+            .collect();
+
+        let open: Vec<&Todo> = todo_list
+            .iter()
+            .filter(|todo| todo.completion_date.is_none())
+            .collect();
+
+        let overdue = open
+            .iter()
+            .filter_map(|todo| Some((*todo, todo.due_date.as_deref().and_then(parse_time)?)))
+            .filter(|(_, due)| *due < now)
+            .sorted_by_key(|(_, due)| *due)
+            .collect();
+
+        let unassigned = open
+            .iter()
+            .filter(|todo| todo.priority == Priority::High as i32)
+            .filter(|todo| !assigned.contains(&(todo.channel_id, todo.id)))
+            .copied()
+            .collect();
+
+        let oldest = open
+            .iter()
+            .filter_map(|todo| Some((*todo, parse_time(&todo.creation_date)?)))
+            .sorted_by_key(|(_, created)| *created)
+            .collect();
+
+        Self {
+            created,
+            completed: completers.len(),
+            completed_by,
+            open: open.len(),
+            overdue,
+            unassigned,
+            oldest,
+        }
+    }
+
+    /// Digest without activity or anything needing attention isn't sent.
+    fn is_empty(&self) -> bool {
+        self.created == 0 && self.completed == 0 && self.open == 0
+    }
+
+    fn embed<'b>(&self, embed: &'b mut CreateEmbed, listed: usize) -> &'b mut CreateEmbed {
+        embed.title("Weekly TODO digest").description(format!(
+            "{} created and {} completed in the last week, {} open.",
+            self.created, self.completed, self.open
+        ));
+
+        if !self.completed_by.is_empty() {
+            let lines = self
+                .completed_by
+                .iter()
+                .map(|(actor, count)| format!("<@{actor}>: {count}"))
+                .collect();
+            embed.field("Completed by", format_list(lines, listed), true);
+        }
+        if !self.overdue.is_empty() {
+            let lines = self
+                .overdue
+                .iter()
+                .map(|(todo, due)| {
+                    format!("{} (due <t:{}:R>)", format_todo(todo), due.unix_timestamp())
+                })
+                .collect();
+            embed.field("Overdue", format_list(lines, listed), false);
+        }
+        if !self.unassigned.is_empty() {
+            let lines = self
+                .unassigned
+                .iter()
+                .map(|todo| format_todo(todo))
+                .collect();
+            embed.field(
+                "Unassigned with high priority",
+                format_list(lines, listed),
+                false,
+            );
+        }
+        if !self.oldest.is_empty() {
+            let lines = self
+                .oldest
+                .iter()
+                .map(|(todo, created)| {
+                    format!(
+                        "{} (created <t:{}:R>)",
+                        format_todo(todo),
+                        created.unix_timestamp()
+                    )
+                })
+                .collect();
+            embed.field("Oldest open", format_list(lines, listed), false);
+        }
+
+        embed
+    }
+}
+
+fn format_todo(todo: &Todo) -> String {
+    let mut text: String = todo.todo.chars().take(MAX_TEXT_LENGTH).collect();
+    if text.len() < todo.todo.len() {
+        text.push('…');
+    }
+    format!("[{}] {text}", todo.id)
+}
+
+/// Joins the first `listed` lines, the rest is only counted.
+fn format_list(lines: Vec<String>, listed: usize) -> String {
+    let more = lines.len().saturating_sub(listed);
+    let mut list = lines.into_iter().take(listed).join("\n");
+    if more > 0 {
+        list = format!("{list}\n*and {more} more*");
+    }
+    list.chars().take(DISCORD_EMBED_FIELD_VALUE_LIMIT).collect()
+}
+
+/// Sends weekly digest to channels with TODOs in guilds with the digest
+/// enabled.
+pub async fn send_digests(ctx_data: &CtxData, http: &Http) -> Result<()> {
+    let now = OffsetDateTime::now_utc();
+    let start = (now - Duration::days(DIGEST_DAYS))
+        .format(&TIME_FORMAT)
+        .unwrap();
+
+    let (todo_list, assigned, completions) = {
+        let conn = &mut ctx_data.db.get()?;
+        let (todo_list, completions) = load_activity(conn, &start)?;
+        let channels: Vec<i64> = todo_list.keys().copied().collect();
+        let assigned: HashSet<(i64, i32)> = get_assignees(conn, &channels)?.into_keys().collect();
+        (todo_list, assigned, completions)
+    };
+
+    for (channel, todos) in &todo_list {
+        let chnl = ChannelId(*channel as u64);
+        let settings = ctx_data.settings.get_bot_settings(http, &chnl).await;
+        if !settings.features.contains(&Feature::WeeklyTodoDigest)
+            || settings.todo_digest.disabled_channels.contains(&chnl)
+        {
+            continue;
+        }
+
+        let channel_completions = completions.get(channel).map_or(&[][..], Vec::as_slice);
+        let digest = Digest::new(todos, &assigned, channel_completions, now);
+        if digest.is_empty() {
+            continue;
+        }
+
+        let listed = settings.todo_digest.listed;
+        let sent = chnl
+            .send_message(http, |message| {
+                message.embed(|embed| digest.embed(embed, listed))
+            })
+            .await;
+        if let Err(e) = sent {
+            debug!("Error while sending TODO digest: {:?}", e);
+        }
+    }
+
+    Ok(())
+}
+
+type ChannelTodos = HashMap<i64, Vec<Todo>>;
+type ChannelCompletions = HashMap<i64, Vec<(i32, i64)>>;
+
+/// Returns TODOs which are open or were completed since `start`, and
+/// completions of those still completed since then, both by channel.
+fn load_activity(
+    conn: &mut SqliteConnection,
+    start: &str,
+) -> QueryResult<(ChannelTodos, ChannelCompletions)> {
+    use crate::schema::{todo_events, todos};
+
+    let todo_list = todos::table
+        .filter(todos::deletion_date.is_null())
+        .filter(
+            todos::completion_date
+                .is_null()
+                .or(todos::completion_date.gt(start)),
+        )
+        .order((todos::channel_id.asc(), todos::id.asc()))
+        .load::<Todo>(conn)?
+        .into_iter()
+        .into_group_map_by(|todo| todo.channel_id);
+
+    let completions = todo_events::table
+        .inner_join(
+            todos::table.on(todos::channel_id
+                .eq(todo_events::channel_id)
+                .and(todos::id.eq(todo_events::todo_id))),
+        )
+        .filter(todo_events::action.eq(TodoAction::Completed.to_string()))
+        .filter(todo_events::creation_date.gt(start))
+        .filter(todos::completion_date.gt(start))
+        .filter(todos::deletion_date.is_null())
+        .order(todo_events::id.asc())
+        .select((
+            todo_events::channel_id,
+            todo_events::todo_id,
+            todo_events::actor,
+        ))
+        .load::<(i64, i32, i64)>(conn)?
+        .into_iter()
+        .into_group_map_by(|(channel, _, _)| *channel)
+        .into_iter()
+        .map(|(channel, events)| {
+            let events = events
+                .into_iter()
+                .map(|(_, todo, actor)| (todo, actor))
+                .collect();
+            (channel, events)
+        })
+        .collect();
+
+    Ok((todo_list, completions))
+}
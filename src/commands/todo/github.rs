@@ -0,0 +1,504 @@
+use diesel::{
+    prelude::*,
+    result::{Error::NotFound, QueryResult},
+};
+use octocrab::{
+    models::{issues::Issue, IssueState},
+    params::{self, issues::Sort},
+    Octocrab,
+};
+use poise::serenity_prelude::{Http, UserId};
+use time::OffsetDateTime;
+use tracing::{debug, warn};
+
+use super::{
+    after_completed, complete_todo, insert_event, next_todo_id, respond_text, uncomplete_todo,
+    Priority, TodoAction,
+};
+use crate::{
+    commands::{parse_time, TIME_FORMAT},
+    ctx_data::CtxData,
+    models::todo::{GithubRepo, NewTodo},
+    Context, Result,
+};
+
+// Maximum number of issues returned by GitHub on a single page
+const ISSUES_PER_PAGE: u8 = 100;
+
+// Maximum length of GitHub issue title
+const ISSUE_TITLE_LIMIT: usize = 256;
+
+/// Manage GitHub repository linked to the channel
+#[allow(clippy::unused_async)]
+#[poise::command(slash_command, subcommands("github_link", "github_unlink"))]
+pub async fn github(_ctx: Context<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// Link GitHub repository to the channel, its issues are synced with TODOs
+#[poise::command(
+    slash_command,
+    rename = "link",
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn github_link(
+    ctx: Context<'_>,
+    #[description = "Repository, e.g. `owner/repo`"] repository: String,
+) -> Result<()> {
+    use crate::schema::{
+        todo_github_repos::dsl::{
+            channel_id as repo_channel_id, last_sync_date, repository as repository_column,
+            todo_github_repos,
+        },
+        todos::dsl::{channel_id, issue, todos},
+    };
+
+    let Some((owner, repo)) = parse_repository(&repository) else {
+        respond_text(ctx, "Invalid repository.".to_string(), true).await;
+        return Ok(());
+    };
+
+    // The bot token can read repositories the members shouldn't see
+    if !ctx
+        .data()
+        .settings
+        .github
+        .is_allowed(&format!("{owner}/{repo}"))
+    {
+        let data = "Repository isn't allowed, the bot owner has to allow it first.".to_string();
+        respond_text(ctx, data, true).await;
+        return Ok(());
+    }
+
+    ctx.defer_ephemeral().await?;
+
+    let found = ctx
+        .data()
+        .settings
+        .github
+        .client()?
+        .repos(owner, repo)
+        .get()
+        .await;
+    let full_name = match found {
+        Ok(found) => found.full_name.unwrap_or_else(|| format!("{owner}/{repo}")),
+        Err(e) => {
+            debug!("{:?}", e);
+            respond_text(ctx, "Repository not found.".to_string(), true).await;
+            return Ok(());
+        }
+    };
+
+    let channel = i64::from(ctx.channel_id());
+
+    let linked: QueryResult<()> = ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+        let previous = todo_github_repos
+            .filter(repo_channel_id.eq(channel))
+            .select(repository_column)
+            .first::<String>(conn)
+            .optional()?;
+        // Issue numbers only make sense in the repository they come from
+        if previous.as_ref() != Some(&full_name) {
+            diesel::update(todos)
+                .filter(channel_id.eq(channel))
+                .set(issue.eq::<Option<i32>>(None))
+                .execute(conn)?;
+        }
+        diesel::insert_into(todo_github_repos)
+            .values((
+                repo_channel_id.eq(channel),
+                repository_column.eq(&full_name),
+                last_sync_date.eq::<Option<String>>(None),
+            ))
+            .on_conflict(repo_channel_id)
+            .do_update()
+            .set((
+                repository_column.eq(&full_name),
+                last_sync_date.eq::<Option<String>>(None),
+            ))
+            .execute(conn)?;
+        Ok(())
+    });
+
+    let data = match linked {
+        Ok(()) => format!(
+            "Channel linked to `{full_name}`, its open issues will be added as TODOs shortly."
+        ),
+        Err(_) => "Linking repository failed.".to_string(),
+    };
+
+    respond_text(ctx, data, true).await;
+
+    Ok(())
+}
+
+/// Unlink GitHub repository from the channel
+#[poise::command(
+    slash_command,
+    rename = "unlink",
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn github_unlink(ctx: Context<'_>) -> Result<()> {
+    use crate::schema::{
+        todo_github_repos::dsl::{channel_id as repo_channel_id, todo_github_repos},
+        todos::dsl::{channel_id, issue, todos},
+    };
+
+    let channel = i64::from(ctx.channel_id());
+
+    let unlinked: QueryResult<String> =
+        ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+            let repository = get_repository(conn, channel)?.ok_or(NotFound)?;
+            diesel::delete(todo_github_repos)
+                .filter(repo_channel_id.eq(channel))
+                .execute(conn)?;
+            diesel::update(todos)
+                .filter(channel_id.eq(channel))
+                .set(issue.eq::<Option<i32>>(None))
+                .execute(conn)?;
+            Ok(repository)
+        });
+
+    let data = match unlinked {
+        Ok(repository) => format!("Channel unlinked from `{repository}`."),
+        Err(NotFound) => "Channel isn't linked to any repository.".to_string(),
+        Err(_) => "Unlinking repository failed.".to_string(),
+    };
+
+    respond_text(ctx, data, true).await;
+
+    Ok(())
+}
+
+/// Parses repository given as `owner/repo` or its GitHub URL.
+fn parse_repository(input: &str) -> Option<(&str, &str)> {
+    let input = input.trim().trim_end_matches('/');
+    let input = input.strip_suffix(".git").unwrap_or(input);
+    let input = input.strip_prefix("https://github.com/").unwrap_or(input);
+
+    let (owner, repo) = input.split_once('/')?;
+    let valid = |name: &str| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+
+    (valid(owner) && valid(repo)).then_some((owner, repo))
+}
+
+fn get_repository(conn: &mut SqliteConnection, channel: i64) -> QueryResult<Option<String>> {
+    use crate::schema::todo_github_repos::dsl::{channel_id, repository, todo_github_repos};
+
+    todo_github_repos
+        .filter(channel_id.eq(channel))
+        .select(repository)
+        .first::<String>(conn)
+        .optional()
+}
+
+/// Opens issue for the TODO in the repository linked to the channel, returns
+/// its number or `None` if the channel isn't linked.
+pub(super) async fn open_issue(ctx: Context<'_>, content: &str) -> Result<Option<i32>> {
+    let repository = get_repository(&mut *ctx.data().db.get()?, i64::from(ctx.channel_id()))?;
+    let Some((owner, repo)) = repository
+        .as_deref()
+        .filter(|repository| ctx.data().settings.github.is_allowed(repository))
+        .and_then(parse_repository)
+    else {
+        return Ok(None);
+    };
+
+    let first_line = content.lines().next().unwrap_or_default();
+    let title: String = first_line.chars().take(ISSUE_TITLE_LIMIT).collect();
+    let body = format!("{content}\n\nOpened from Discord by {}.", ctx.author().name);
+
+    let issue = ctx
+        .data()
+        .settings
+        .github
+        .client()?
+        .issues(owner, repo)
+        .create(title)
+        .body(body)
+        .send()
+        .await?;
+
+    Ok(Some(issue.number as i32))
+}
+
+/// Closes or reopens issue of the TODO if it has any, failures are only
+/// logged as the TODO itself is already changed.
+pub(super) async fn update_issue(ctx_data: &CtxData, channel: i64, todo: i32, state: IssueState) {
+    if let Err(e) = try_update_issue(ctx_data, channel, todo, state).await {
+        warn!("Updating GitHub issue of TODO [{todo}] failed: {e:?}");
+    }
+}
+
+/// Closes issue which no longer has a TODO linked to it, failures are only
+/// logged.
+pub(super) async fn close_issue(ctx_data: &CtxData, channel: i64, number: i32) {
+    if let Err(e) = set_issue_state(ctx_data, channel, number, IssueState::Closed).await {
+        warn!("Closing GitHub issue #{number} failed: {e:?}");
+    }
+}
+
+async fn try_update_issue(
+    ctx_data: &CtxData,
+    channel: i64,
+    todo: i32,
+    state: IssueState,
+) -> Result<()> {
+    use crate::schema::todos::dsl::{channel_id, id, issue, todos};
+
+    let number = todos
+        .filter(channel_id.eq(channel))
+        .filter(id.eq(todo))
+        .select(issue)
+        .first::<Option<i32>>(&mut ctx_data.db.get()?)?;
+
+    match number {
+        Some(number) => set_issue_state(ctx_data, channel, number, state).await,
+        None => Ok(()),
+    }
+}
+
+async fn set_issue_state(
+    ctx_data: &CtxData,
+    channel: i64,
+    number: i32,
+    state: IssueState,
+) -> Result<()> {
+    let repository = get_repository(&mut *ctx_data.db.get()?, channel)?;
+    let Some((owner, repo)) = repository
+        .as_deref()
+        .filter(|repository| ctx_data.settings.github.is_allowed(repository))
+        .and_then(parse_repository)
+    else {
+        return Ok(());
+    };
+
+    ctx_data
+        .settings
+        .github
+        .client()?
+        .issues(owner, repo)
+        .update(number as u64)
+        .state(state)
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+/// Mirrors issues opened, closed and reopened in the linked repositories to
+/// the TODOs of their channels, `actor` is recorded as the author of changes.
+pub async fn sync_issues(ctx_data: &CtxData, http: &Http, actor: UserId) -> Result<()> {
+    use crate::schema::todo_github_repos::dsl::todo_github_repos;
+
+    let repos = todo_github_repos.load::<GithubRepo>(&mut ctx_data.db.get()?)?;
+    if repos.is_empty() {
+        return Ok(());
+    }
+
+    let client = ctx_data.settings.github.client()?;
+
+    // Repositories linked before they were disallowed aren't synced anymore
+    let allowed = repos
+        .iter()
+        .filter(|repo| ctx_data.settings.github.is_allowed(&repo.repository));
+    let (mut completed, mut uncompleted) = (vec![], vec![]);
+    for repo in allowed {
+        match sync_repository(ctx_data, &client, actor, repo).await {
+            Ok(changed) => {
+                for (todo, done) in changed {
+                    let key = (repo.channel_id, todo);
+                    if done {
+                        completed.push(key);
+                    } else {
+                        uncompleted.push(key);
+                    }
+                }
+            }
+            Err(e) => warn!(
+                "Syncing GitHub repository `{}` failed: {e:?}",
+                repo.repository
+            ),
+        }
+    }
+
+    after_completed(ctx_data, http, &completed, true).await;
+    after_completed(ctx_data, http, &uncompleted, false).await;
+
+    Ok(())
+}
+
+/// Returns IDs of the TODOs with changed completion and whether they were
+/// completed.
+async fn sync_repository(
+    ctx_data: &CtxData,
+    client: &Octocrab,
+    actor: UserId,
+    repo: &GithubRepo,
+) -> Result<Vec<(i32, bool)>> {
+    use crate::schema::todo_github_repos::dsl::{
+        channel_id, last_sync_date, repository, todo_github_repos,
+    };
+
+    let Some((owner, name)) = parse_repository(&repo.repository) else {
+        return Ok(vec![]);
+    };
+
+    // Taken before fetching to not miss issues updated in the meantime
+    let now = OffsetDateTime::now_utc().format(&TIME_FORMAT).unwrap();
+    let since = repo.last_sync_date.as_deref().and_then(parse_time);
+    let issues = fetch_issues(client, owner, name, since).await?;
+
+    let synced: QueryResult<Vec<(i32, bool)>> = ctx_data.db.get()?.immediate_transaction(|conn| {
+        // The channel could have been unlinked while fetching
+        let updated = diesel::update(todo_github_repos)
+            .filter(channel_id.eq(repo.channel_id))
+            .filter(repository.eq(&repo.repository))
+            .set(last_sync_date.eq(&now))
+            .execute(conn)?;
+        if updated == 0 {
+            return Ok(vec![]);
+        }
+
+        let mut changed = vec![];
+        for issue in issues.iter().rev() {
+            changed.extend(apply_issue(conn, actor, repo.channel_id, issue)?);
+        }
+        Ok(changed)
+    });
+    let changed = synced?;
+
+    debug!(
+        "Synced {} issues of `{}` in channel {}",
+        issues.len(),
+        repo.repository,
+        repo.channel_id
+    );
+
+    Ok(changed)
+}
+
+/// Fetches issues updated since the last sync, the most recently updated
+/// first. Only open issues are fetched on the first sync as there are no
+/// TODOs to close yet.
+async fn fetch_issues(
+    client: &Octocrab,
+    owner: &str,
+    repo: &str,
+    since: Option<OffsetDateTime>,
+) -> octocrab::Result<Vec<Issue>> {
+    let state = if since.is_some() {
+        params::State::All
+    } else {
+        params::State::Open
+    };
+    let since = since.map(OffsetDateTime::unix_timestamp);
+
+    let mut page = client
+        .issues(owner, repo)
+        .list()
+        .state(state)
+        .sort(Sort::Updated)
+        .direction(params::Direction::Descending)
+        .per_page(ISSUES_PER_PAGE)
+        .send()
+        .await?;
+
+    let mut issues = vec![];
+    loop {
+        let mut reached_since = false;
+        for issue in page.take_items() {
+            if since.is_some_and(|since| issue.updated_at.timestamp() < since) {
+                reached_since = true;
+                break;
+            }
+            // Pull requests are returned as issues as well
+            if issue.pull_request.is_none() {
+                issues.push(issue);
+            }
+        }
+
+        if reached_since {
+            break;
+        }
+        match client.get_page::<Issue>(&page.next).await? {
+            Some(next) => page = next,
+            None => break,
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Adds TODO for newly opened issue or changes completion of the TODO linked
+/// to the issue to match its state. Returns ID of the TODO with changed
+/// completion and whether it was completed.
+fn apply_issue(
+    conn: &mut SqliteConnection,
+    actor: UserId,
+    channel: i64,
+    issue: &Issue,
+) -> QueryResult<Option<(i32, bool)>> {
+    use crate::schema::todos::dsl::{
+        channel_id, completion_date, deletion_date, id, issue as issue_column, todos,
+    };
+
+    let number = issue.number as i32;
+    let open = issue.state == IssueState::Open;
+    let time = OffsetDateTime::now_utc().format(&TIME_FORMAT).unwrap();
+
+    let linked = todos
+        .filter(channel_id.eq(channel))
+        .filter(issue_column.eq(number))
+        .select((
+            id,
+            completion_date.is_not_null(),
+            deletion_date.is_not_null(),
+        ))
+        .first::<(i32, bool, bool)>(conn)
+        .optional()?;
+
+    match linked {
+        None if open => {
+            let new_id = next_todo_id(conn, channel)?;
+            let new_todo = NewTodo {
+                channel_id: &channel,
+                id: &new_id,
+                todo: &issue.title,
+                creation_date: &time,
+                completion_date: None,
+                priority: Priority::default() as i32,
+                due_date: None,
+                recurrence: None,
+                issue: Some(number),
+                message_link: None,
+            };
+            diesel::insert_into(todos).values(&new_todo).execute(conn)?;
+            insert_event(
+                conn,
+                actor,
+                channel,
+                new_id,
+                TodoAction::Created,
+                None,
+                Some(&issue.title),
+            )?;
+            Ok(None)
+        }
+        // Deleted TODOs stay deleted, unlike completed ones
+        Some((todo, completed, false)) if completed == open => {
+            if open {
+                uncomplete_todo(conn, actor, channel, todo)?;
+            } else {
+                complete_todo(conn, actor, channel, todo)?;
+            }
+            Ok(Some((todo, !open)))
+        }
+        _ => Ok(None),
+    }
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use diesel::{
+    prelude::*,
+    result::{DatabaseErrorKind::UniqueViolation, Error::DatabaseError},
+};
+use poise::serenity_prelude::{Message, MessageBuilder};
+use time::OffsetDateTime;
+use tracing::debug;
+
+use super::{
+    log_event, next_todo_id, parse_due_date, respond_text, thread, Priority, TodoAction,
+    ID_CONFLICT, MODAL_TIMEOUT,
+};
+use crate::{
+    commands::TIME_FORMAT, ctx_data::CtxData, models::todo::NewTodo, Context, Error, Result,
+};
+
+#[derive(Debug, poise::Modal)]
+#[name = "Create TODO"]
+struct TodoCreationModal {
+    #[name = "Content"]
+    #[paragraph]
+    #[max_length = 1024]
+    content: String,
+    #[name = "Due date"]
+    #[placeholder = "e.g. 2024-05-01 14:00 or in 3 days"]
+    due: Option<String>,
+}
+
+/// Create TODO from the message
+#[poise::command(context_menu_command = "Create TODO", guild_only)]
+pub async fn todo_from_message(
+    ctx: poise::ApplicationContext<'_, Arc<CtxData>, Error>,
+    #[description = "Message to create TODO from"] message: Message,
+) -> Result<()> {
+    use crate::schema::todos::dsl::todos;
+
+    // Longer messages are cut, the modal doesn't accept longer defaults
+    let defaults = TodoCreationModal {
+        content: message.content.chars().take(1024).collect(),
+        due: None,
+    };
+    let submitted = poise::execute_modal(ctx, Some(defaults), Some(MODAL_TIMEOUT)).await;
+    let submitted = match submitted {
+        Ok(Some(submitted)) => submitted,
+        Ok(None) => return Ok(()),
+        Err(e) => {
+            debug!("{:?}", e);
+            return Ok(());
+        }
+    };
+
+    let ctx = Context::from(ctx);
+    let content = submitted.content.trim().to_string();
+    let due_date = submitted.due.as_deref().map(parse_due_date);
+
+    let mut thread_todo = None;
+    let data = if content.is_empty() {
+        "Content can't be empty.".to_string()
+    } else if content.chars().count() > 1024 {
+        "Content can't have more than 1024 characters.".to_string()
+    } else if let Some(None) = due_date {
+        "Invalid due date.".to_string()
+    } else {
+        let time = OffsetDateTime::now_utc().format(&TIME_FORMAT).unwrap();
+        let channel = i64::from(ctx.channel_id());
+        // Messages resolved for the command don't carry their guild
+        let link = message.id.link(message.channel_id, ctx.guild_id());
+
+        let result = ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+            let new_id = next_todo_id(conn, channel)?;
+            let new_todo = NewTodo {
+                channel_id: &channel,
+                id: &new_id,
+                todo: &content,
+                creation_date: &time,
+                completion_date: None,
+                priority: Priority::default() as i32,
+                due_date: due_date.flatten(),
+                recurrence: None,
+                issue: None,
+                message_link: Some(link),
+            };
+            diesel::insert_into(todos).values(&new_todo).execute(conn)?;
+            log_event(
+                conn,
+                ctx,
+                channel,
+                new_id,
+                TodoAction::Created,
+                None,
+                Some(&content),
+            )?;
+            Ok((new_id, thread::creates_threads(conn, channel)?))
+        });
+
+        match result {
+            Ok((new_id, thread)) => {
+                thread_todo = thread.then_some(new_id);
+                MessageBuilder::new()
+                    .push(format!("TODO [{new_id}] ("))
+                    .push_mono_safe(&content)
+                    .push(") added from the message.")
+                    .build()
+            }
+            Err(DatabaseError(UniqueViolation, _)) => ID_CONFLICT.to_string(),
+            Err(_) => "Adding TODO failed.".to_string(),
+        }
+    };
+
+    let Some(new_id) = thread_todo else {
+        respond_text(ctx, data, false).await;
+        return Ok(());
+    };
+
+    // The thread is started on the response, so it has to be sent first
+    let reply = ctx
+        .send(|reply| reply.embed(|embed| embed.description(data)))
+        .await?;
+    let message = reply.message().await?;
+    if let Err(e) = thread::create_thread(ctx, &message, new_id, &content).await {
+        debug!("Creating TODO thread failed: {:?}", e);
+        respond_text(ctx, "Creating thread failed.".to_string(), true).await;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,423 @@
+use std::{fmt, str::FromStr};
+
+use itertools::Itertools;
+use poise::{
+    async_trait,
+    serenity_prelude::{
+        json, CommandOptionType, CreateApplicationCommandOption, Mention, Mentionable, RoleId,
+        UserId,
+    },
+    SlashArgError, SlashArgument,
+};
+
+// Limits how many TODOs a single command can change, so that ranges like
+// `1-1000000` don't make it run for ages
+const MAX_SELECTED: usize = 100;
+
+// Limits how many assignees can be given at once
+const MAX_ASSIGNEES: usize = 10;
+
+#[derive(Debug)]
+pub struct ParseError {
+    what: &'static str,
+    reason: String,
+}
+
+impl ParseError {
+    fn ids(reason: String) -> Self {
+        Self {
+            what: "TODO IDs",
+            reason,
+        }
+    }
+
+    fn assignees(reason: String) -> Self {
+        Self {
+            what: "assignees",
+            reason,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {}: {}", self.what, self.reason)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// TODO IDs given as a comma separated list of IDs and ranges, e.g.
+/// `3,5,9-12`. The IDs are sorted and without duplicates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdSelection(Vec<i32>);
+
+impl IdSelection {
+    pub fn ids(&self) -> &[i32] {
+        &self.0
+    }
+
+    fn parse_id(input: &str) -> Result<i32, ParseError> {
+        input
+            .trim()
+            .parse::<i32>()
+            .ok()
+            .filter(|id| *id >= 0)
+            .ok_or_else(|| ParseError::ids(format!("`{}` isn't an ID", input.trim())))
+    }
+}
+
+impl FromStr for IdSelection {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ids = vec![];
+
+        for part in s.split(',').filter(|part| !part.trim().is_empty()) {
+            match part.split_once('-') {
+                Some((start, end)) => {
+                    let (start, end) = (Self::parse_id(start)?, Self::parse_id(end)?);
+                    if start > end {
+                        return Err(ParseError::ids(format!(
+                            "range `{}` is reversed",
+                            part.trim()
+                        )));
+                    }
+                    if (end - start) as usize >= MAX_SELECTED {
+                        return Err(ParseError::ids(format!(
+                            "at most {MAX_SELECTED} can be selected"
+                        )));
+                    }
+                    ids.extend(start..=end);
+                }
+                None => ids.push(Self::parse_id(part)?),
+            }
+        }
+
+        let ids: Vec<i32> = ids.into_iter().sorted().dedup().collect();
+        if ids.is_empty() {
+            return Err(ParseError::ids("none given".to_string()));
+        }
+        if ids.len() > MAX_SELECTED {
+            return Err(ParseError::ids(format!(
+                "at most {MAX_SELECTED} can be selected"
+            )));
+        }
+
+        Ok(Self(ids))
+    }
+}
+
+#[async_trait]
+impl SlashArgument for IdSelection {
+    async fn extract<'life0, 'life1, 'life2>(
+        _ctx: &'life0 poise::serenity_prelude::Context,
+        _interaction: poise::ApplicationCommandOrAutocompleteInteraction<'life1>,
+        value: &'life2 poise::serenity_prelude::json::Value,
+    ) -> core::result::Result<Self, SlashArgError>
+    where
+        'life0: 'async_trait,
+        'life1: 'async_trait,
+        'life2: 'async_trait,
+    {
+        // Plain numbers are accepted too, in case the command wasn't
+        // registered again since it took a single ID
+        let input = match value {
+            json::Value::String(input) => input.clone(),
+            json::Value::Number(num) => num.to_string(),
+            _ => return Err(SlashArgError::CommandStructureMismatch("expected string")),
+        };
+
+        parse_argument(input)
+    }
+
+    fn create(builder: &mut CreateApplicationCommandOption) {
+        builder.kind(CommandOptionType::String);
+    }
+}
+
+/// Member or role the TODO is assigned to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Assignee {
+    Member(UserId),
+    Role(RoleId),
+}
+
+impl Assignee {
+    pub fn from_row(id: i64, is_role: bool) -> Self {
+        if is_role {
+            Self::Role(RoleId(id as u64))
+        } else {
+            Self::Member(UserId(id as u64))
+        }
+    }
+
+    pub fn id(self) -> i64 {
+        match self {
+            Self::Member(user) => user.0 as i64,
+            Self::Role(role) => role.0 as i64,
+        }
+    }
+
+    pub fn is_role(self) -> bool {
+        matches!(self, Self::Role(_))
+    }
+
+    pub fn mention(self) -> Mention {
+        match self {
+            Self::Member(user) => user.mention(),
+            Self::Role(role) => role.mention(),
+        }
+    }
+}
+
+impl FromStr for Assignee {
+    type Err = ParseError;
+
+    /// Parses user or role mention, plain IDs are taken as user IDs.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseError::assignees(format!("`{s}` isn't a member or a role"));
+
+        let Some(mention) = s.strip_prefix("<@").and_then(|m| m.strip_suffix('>')) else {
+            return s
+                .parse()
+                .map(|id| Self::Member(UserId(id)))
+                .map_err(|_| invalid());
+        };
+        match mention.strip_prefix('&') {
+            Some(role) => role.parse().map(|id| Self::Role(RoleId(id))),
+            None => mention
+                .trim_start_matches('!')
+                .parse()
+                .map(|id| Self::Member(UserId(id))),
+        }
+        .map_err(|_| invalid())
+    }
+}
+
+#[async_trait]
+impl SlashArgument for Assignee {
+    async fn extract<'life0, 'life1, 'life2>(
+        _ctx: &'life0 poise::serenity_prelude::Context,
+        interaction: poise::ApplicationCommandOrAutocompleteInteraction<'life1>,
+        value: &'life2 poise::serenity_prelude::json::Value,
+    ) -> core::result::Result<Self, SlashArgError>
+    where
+        'life0: 'async_trait,
+        'life1: 'async_trait,
+        'life2: 'async_trait,
+    {
+        let id = value
+            .as_str()
+            .and_then(|id| id.parse().ok())
+            .ok_or(SlashArgError::CommandStructureMismatch("expected ID"))?;
+
+        // Mentionable option gives just the ID, the resolved data tell whether
+        // it's a role
+        let roles = &interaction.data().resolved.roles;
+        Ok(if roles.contains_key(&RoleId(id)) {
+            Self::Role(RoleId(id))
+        } else {
+            Self::Member(UserId(id))
+        })
+    }
+
+    fn create(builder: &mut CreateApplicationCommandOption) {
+        builder.kind(CommandOptionType::Mentionable);
+    }
+}
+
+/// Members and roles given by their mentions or IDs separated by spaces or
+/// commas, e.g. `@alice @oncall`. They are without duplicates, in the given
+/// order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssigneeSelection(Vec<Assignee>);
+
+impl AssigneeSelection {
+    pub fn assignees(&self) -> &[Assignee] {
+        &self.0
+    }
+}
+
+impl FromStr for AssigneeSelection {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let assignees: Vec<Assignee> = s
+            .split([' ', ','])
+            .filter(|part| !part.is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unique()
+            .collect();
+
+        if assignees.is_empty() {
+            return Err(ParseError::assignees("none given".to_string()));
+        }
+        if assignees.len() > MAX_ASSIGNEES {
+            return Err(ParseError::assignees(format!(
+                "at most {MAX_ASSIGNEES} can be given"
+            )));
+        }
+
+        Ok(Self(assignees))
+    }
+}
+
+#[async_trait]
+impl SlashArgument for AssigneeSelection {
+    async fn extract<'life0, 'life1, 'life2>(
+        _ctx: &'life0 poise::serenity_prelude::Context,
+        _interaction: poise::ApplicationCommandOrAutocompleteInteraction<'life1>,
+        value: &'life2 poise::serenity_prelude::json::Value,
+    ) -> core::result::Result<Self, SlashArgError>
+    where
+        'life0: 'async_trait,
+        'life1: 'async_trait,
+        'life2: 'async_trait,
+    {
+        let json::Value::String(input) = value else {
+            return Err(SlashArgError::CommandStructureMismatch("expected string"));
+        };
+
+        parse_argument(input.clone())
+    }
+
+    fn create(builder: &mut CreateApplicationCommandOption) {
+        builder.kind(CommandOptionType::String);
+    }
+}
+
+fn parse_argument<T: FromStr<Err = ParseError>>(input: String) -> Result<T, SlashArgError> {
+    input.parse().map_err(|e| SlashArgError::Parse {
+        error: Box::new(e),
+        input,
+    })
+}
+
+/// Formats the IDs compactly, consecutive ones are joined into ranges.
+pub fn format_ids(ids: &[i32]) -> String {
+    let mut ranges: Vec<(i32, i32)> = vec![];
+    for &id in ids {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == id => *end = id,
+            _ => ranges.push((id, id)),
+        }
+    }
+
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}-{end}")
+            }
+        })
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(input: &str) -> Result<Vec<i32>, ParseError> {
+        input.parse::<IdSelection>().map(|s| s.ids().to_vec())
+    }
+
+    fn assignees(input: &str) -> Result<Vec<Assignee>, ParseError> {
+        input
+            .parse::<AssigneeSelection>()
+            .map(|s| s.assignees().to_vec())
+    }
+
+    #[test]
+    fn id_selection() {
+        assert_eq!(ids("3").unwrap(), [3]);
+        assert_eq!(ids("3,5,9-12").unwrap(), [3, 5, 9, 10, 11, 12]);
+        assert_eq!(ids(" 3 , 5 - 6 ,").unwrap(), [3, 5, 6]);
+        assert_eq!(ids("7-7").unwrap(), [7]);
+    }
+
+    #[test]
+    fn id_selection_sorted_without_duplicates() {
+        assert_eq!(ids("5,3,5,4-6,3").unwrap(), [3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn id_selection_invalid() {
+        for input in ["", " , ", "x", "3,x", "12-9", "-5", "5-", "3-4-5", "1.5"] {
+            assert!(ids(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn id_selection_limit() {
+        assert_eq!(ids("1-100").unwrap().len(), MAX_SELECTED);
+        assert!(ids("1-101").is_err());
+        assert!(ids("1-1000000").is_err());
+        // Limit applies to all the IDs together as well
+        assert!(ids("1-60,101-160").is_err());
+        // Duplicates count only once
+        assert_eq!(ids("1-100,50-100").unwrap().len(), MAX_SELECTED);
+    }
+
+    #[test]
+    fn assignee_mentions_and_ids() {
+        assert_eq!(
+            "<@123>".parse::<Assignee>().unwrap(),
+            Assignee::Member(UserId(123))
+        );
+        assert_eq!(
+            "<@!123>".parse::<Assignee>().unwrap(),
+            Assignee::Member(UserId(123))
+        );
+        assert_eq!(
+            "<@&456>".parse::<Assignee>().unwrap(),
+            Assignee::Role(RoleId(456))
+        );
+        // Plain IDs are taken as members
+        assert_eq!(
+            "456".parse::<Assignee>().unwrap(),
+            Assignee::Member(UserId(456))
+        );
+        for input in ["", "@alice", "<@alice>", "<#123>", "<@&>", "<@123"] {
+            assert!(input.parse::<Assignee>().is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn assignee_selection() {
+        assert_eq!(
+            assignees("<@1> <@&2>,3,, <@!1>").unwrap(),
+            [
+                Assignee::Member(UserId(1)),
+                Assignee::Role(RoleId(2)),
+                Assignee::Member(UserId(3)),
+            ]
+        );
+        // Role and member with the same ID are different assignees
+        assert_eq!(assignees("<@&1> <@1>").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn assignee_selection_invalid() {
+        assert!(assignees("").is_err());
+        assert!(assignees(" , ").is_err());
+        assert!(assignees("<@1> alice").is_err());
+
+        let many = (1..=11).map(|id| format!("<@{id}>")).join(" ");
+        assert!(assignees(&many).is_err());
+        let repeated = std::iter::repeat_n("<@1>", 11).join(" ");
+        assert_eq!(assignees(&repeated).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn format_ids_compacts_ranges() {
+        assert_eq!(format_ids(&[]), "");
+        assert_eq!(format_ids(&[3]), "3");
+        assert_eq!(format_ids(&[1, 2]), "1-2");
+        assert_eq!(format_ids(&[1, 2, 3, 5, 7, 8, 9, 11]), "1-3, 5, 7-9, 11");
+    }
+}
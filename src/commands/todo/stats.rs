@@ -0,0 +1,282 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use diesel::prelude::*;
+use itertools::Itertools;
+use poise::serenity_prelude::{AttachmentType, CreateEmbed};
+use time::{Duration, OffsetDateTime};
+use tracing::debug;
+
+use super::{get_assignees, get_channels, respond_text, Assignee, Priority, Scope};
+use crate::{
+    chart::{self, Series},
+    commands::parse_time,
+    models::todo::Todo,
+    Context, Result,
+};
+
+// Number of assignees listed in the throughput
+const MAX_ASSIGNEES: usize = 10;
+
+const CHART_FILENAME: &str = "burndown.png";
+
+#[derive(Debug, Clone, Copy, Default, poise::ChoiceParameter)]
+pub enum Period {
+    #[name = "Week"]
+    Week,
+    #[default]
+    #[name = "Month"]
+    Month,
+    #[name = "Quarter"]
+    Quarter,
+    #[name = "Year"]
+    Year,
+}
+
+impl Period {
+    fn days(self) -> usize {
+        match self {
+            Period::Week => 7,
+            Period::Month => 30,
+            Period::Quarter => 90,
+            Period::Year => 365,
+        }
+    }
+}
+
+/// Statistics of TODOs over the period ending now.
+struct Stats {
+    created: usize,
+    completed: usize,
+    average_completion: Option<Duration>,
+    throughput: Vec<(Option<Assignee>, usize)>,
+    open_by_priority: [usize; Priority::VALUES.len()],
+    // Number of open TODOs at the end of each day of the period
+    burndown: Vec<(OffsetDateTime, u32)>,
+}
+
+impl Stats {
+    fn new(
+        todo_list: &[Todo],
+        assignees: &HashMap<(i64, i32), Vec<Assignee>>,
+        period: Period,
+        now: OffsetDateTime,
+    ) -> Self {
+        let start = now - Duration::days(period.days() as i64);
+        let dates: Vec<(OffsetDateTime, Option<OffsetDateTime>, &Todo)> = todo_list
+            .iter()
+            .filter_map(|todo| {
+                let created = parse_time(&todo.creation_date)?;
+                let completed = todo.completion_date.as_deref().and_then(parse_time);
+                Some((created, completed, todo))
+            })
+            .collect();
+
+        let created = dates
+            .iter()
+            .filter(|(created, _, _)| *created > start)
+            .count();
+
+        let completed_in_period: Vec<_> = dates
+            .iter()
+            .filter(|(_, completed, _)| completed.is_some_and(|c| c > start))
+            .collect();
+
+        let durations: Vec<Duration> = completed_in_period
+            .iter()
+            .filter_map(|(created, completed, _)| completed.map(|c| c - *created))
+            .collect();
+        let average_completion = (!durations.is_empty())
+            .then(|| durations.iter().sum::<Duration>() / durations.len() as u32);
+
+        // TODOs with multiple assignees count for each of them
+        let throughput = completed_in_period
+            .iter()
+            .flat_map(
+                |(_, _, todo)| match assignees.get(&(todo.channel_id, todo.id)) {
+                    Some(assignees) => assignees.iter().copied().map(Some).collect(),
+                    None => vec![None],
+                },
+            )
+            .counts()
+            .into_iter()
+            .sorted_by_key(|(assignee, count)| (std::cmp::Reverse(*count), *assignee))
+            .collect();
+
+        let mut open_by_priority = [0; Priority::VALUES.len()];
+        for (_, _, todo) in dates.iter().filter(|(_, completed, _)| completed.is_none()) {
+            if let Some(count) = open_by_priority.get_mut(todo.priority as usize) {
+                *count += 1;
+            }
+        }
+
+        let burndown = (0..=period.days())
+            .map(|day| {
+                let end = start + Duration::days(day as i64);
+                let open = dates
+                    .iter()
+                    .filter(|(created, completed, _)| {
+                        *created <= end && completed.is_none_or(|c| c > end)
+                    })
+                    .count();
+                (end, open as u32)
+            })
+            .collect();
+
+        Self {
+            created,
+            completed: completed_in_period.len(),
+            average_completion,
+            throughput,
+            open_by_priority,
+            burndown,
+        }
+    }
+
+    fn chart(&self) -> Result<Vec<u8>> {
+        let labels: Vec<String> = self
+            .burndown
+            .iter()
+            .map(|(date, _)| format!("{:02}-{:02}", u8::from(date.month()), date.day()))
+            .collect();
+        let values: Vec<u32> = self.burndown.iter().map(|(_, open)| *open).collect();
+
+        chart::line_chart(
+            &labels,
+            &[Series {
+                values: &values,
+                color: chart::RED,
+            }],
+        )
+    }
+
+    fn embed<'a>(&self, embed: &'a mut CreateEmbed, title: String) -> &'a mut CreateEmbed {
+        let average = self
+            .average_completion
+            .map_or_else(|| "-".to_string(), format_duration);
+
+        let throughput = if self.throughput.is_empty() {
+            "Nothing completed.".to_string()
+        } else {
+            self.throughput
+                .iter()
+                .take(MAX_ASSIGNEES)
+                .map(|(assignee, count)| match assignee {
+                    Some(assignee) => format!("{}: {count}", assignee.mention()),
+                    None => format!("no one: {count}"),
+                })
+                .join("\n")
+        };
+
+        let open = Priority::VALUES
+            .iter()
+            .zip(self.open_by_priority)
+            .rev()
+            .map(|(priority, count)| format!("{priority}: {count}"))
+            .join("\n");
+
+        embed
+            .title(title)
+            .field("Created", self.created, true)
+            .field("Completed", self.completed, true)
+            .field("Average time to complete", average, true)
+            .field("Completed by", throughput, true)
+            .field("Open by priority", open, true)
+            .image(format!("attachment://{CHART_FILENAME}"))
+            .footer(|f| f.text("Chart shows number of open TODOs at the end of each day"))
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let days = duration.whole_days();
+    let hours = duration.whole_hours() % 24;
+    let minutes = duration.whole_minutes() % 60;
+
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+/// Show TODO statistics with burndown chart
+#[poise::command(slash_command, guild_only)]
+pub async fn stats(
+    ctx: Context<'_>,
+    #[description = "Period the statistics are computed over, the last month by default"]
+    period: Option<Period>,
+    #[description = "Include all channels of the server"]
+    #[flag]
+    all_channels: bool,
+) -> Result<()> {
+    use crate::schema::todos::dsl::{channel_id, deletion_date, todos};
+
+    let period = period.unwrap_or_default();
+    let scope = if all_channels {
+        Scope::Guild
+    } else {
+        Scope::Channel
+    };
+
+    let Some(channels) = get_channels(ctx, scope).await else {
+        respond_text(ctx, "Not found.".to_string(), true).await;
+        return Ok(());
+    };
+
+    let (todo_list, assignees) = {
+        let conn = &mut ctx.data().db.get()?;
+        let todo_list = todos
+            .filter(channel_id.eq_any(&channels))
+            .filter(deletion_date.is_null())
+            .load::<Todo>(conn);
+        (todo_list, get_assignees(conn, &channels))
+    };
+
+    let Ok((todo_list, assignees)) = todo_list.and_then(|todo_list| Ok((todo_list, assignees?)))
+    else {
+        respond_text(ctx, "Computing statistics failed.".to_string(), true).await;
+        return Ok(());
+    };
+
+    let place = match scope {
+        Scope::Channel => "channel",
+        Scope::Guild => "server",
+    };
+
+    if todo_list.is_empty() {
+        respond_text(ctx, format!("There are no TODOs in this {place}."), true).await;
+        return Ok(());
+    }
+
+    let stats = Stats::new(&todo_list, &assignees, period, OffsetDateTime::now_utc());
+    let title = format!(
+        "TODO statistics of this {place} in the last {}",
+        period.name().to_lowercase()
+    );
+    let chart = match stats.chart() {
+        Ok(chart) => chart,
+        Err(e) => {
+            debug!("{:?}", e);
+            respond_text(ctx, "Drawing chart failed.".to_string(), true).await;
+            return Ok(());
+        }
+    };
+
+    let response = ctx
+        .send(|reply| {
+            reply
+                .embed(|embed| stats.embed(embed, title))
+                .attachment(AttachmentType::Bytes {
+                    data: Cow::Owned(chart),
+                    filename: CHART_FILENAME.to_string(),
+                })
+        })
+        .await;
+
+    if let Err(e) = response {
+        debug!("{:?}", e);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use diesel::{prelude::*, result::QueryResult};
+use poise::serenity_prelude::{ChannelId, Http, Message};
+use tracing::debug;
+
+use super::respond_text;
+use crate::{
+    commands::DISCORD_CHANNEL_NAME_LIMIT,
+    ctx_data::CtxData,
+    models::todo::{ChannelSettings, NewChannelSettings},
+    Context, Result,
+};
+
+/// Configure whether TODOs added in the channel get their own threads
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_CHANNELS")]
+pub async fn threads(
+    ctx: Context<'_>,
+    #[description = "Create thread for each TODO added in the channel by default"] enabled: bool,
+) -> Result<()> {
+    use crate::schema::todo_channels::dsl::{channel_id, todo_channels};
+
+    let channel = i64::from(ctx.channel_id());
+    let new_settings = NewChannelSettings {
+        channel_id: &channel,
+        reminders_enabled: None,
+        reminder_interval: None,
+        reminder_time: None,
+        reminder_quiet_days: None,
+        create_threads: Some(enabled),
+    };
+
+    let result = diesel::insert_into(todo_channels)
+        .values(&new_settings)
+        .on_conflict(channel_id)
+        .do_update()
+        .set(&new_settings)
+        .execute(&mut ctx.data().db.get()?);
+
+    let data = match result {
+        Ok(_) if enabled => "TODOs added in this channel get their own threads.".to_string(),
+        Ok(_) => "TODOs added in this channel don't get their own threads.".to_string(),
+        Err(_) => "Configuring threads failed.".to_string(),
+    };
+
+    respond_text(ctx, data, true).await;
+
+    Ok(())
+}
+
+/// Returns whether TODOs added in the channel get their own threads when not
+/// told otherwise.
+pub(super) fn creates_threads(conn: &mut SqliteConnection, channel: i64) -> QueryResult<bool> {
+    use crate::schema::todo_channels::dsl::{channel_id, todo_channels};
+
+    let settings = todo_channels
+        .filter(channel_id.eq(channel))
+        .first::<ChannelSettings>(conn)
+        .optional()?;
+
+    Ok(settings
+        .and_then(|settings| settings.create_threads)
+        .unwrap_or(false))
+}
+
+/// Starts thread for the TODO on the message announcing it and stores its ID.
+pub(super) async fn create_thread(
+    ctx: Context<'_>,
+    message: &Message,
+    todo_id: i32,
+    text: &str,
+) -> Result<ChannelId> {
+    use crate::schema::todos::dsl::{channel_id, id, thread_id, todos};
+
+    let name: String = format!("[{todo_id}] {text}")
+        .chars()
+        .take(DISCORD_CHANNEL_NAME_LIMIT)
+        .collect();
+    let thread = message
+        .channel_id
+        .create_public_thread(ctx, message.id, |thread| thread.name(name))
+        .await?;
+
+    diesel::update(todos)
+        .filter(channel_id.eq(i64::from(message.channel_id)))
+        .filter(id.eq(todo_id))
+        .set(thread_id.eq(i64::from(thread.id)))
+        .execute(&mut ctx.data().db.get()?)?;
+
+    Ok(thread.id)
+}
+
+/// Archives threads of the TODOs once they are completed, or unarchives them
+/// once they are uncompleted.
+pub(super) async fn set_archived(
+    ctx_data: &CtxData,
+    http: &Http,
+    todo_keys: &[(i64, i32)],
+    archived: bool,
+) {
+    let threads = match ctx_data.db.get() {
+        Ok(mut conn) => get_threads(&mut conn, todo_keys),
+        Err(e) => {
+            debug!("{:?}", e);
+            return;
+        }
+    };
+
+    let threads = match threads {
+        Ok(threads) => threads,
+        Err(e) => {
+            debug!("{:?}", e);
+            return;
+        }
+    };
+
+    for thread in threads {
+        let edited = thread
+            .edit_thread(http, |edit| edit.archived(archived))
+            .await;
+        if let Err(e) = edited {
+            debug!("Error while archiving TODO thread: {:?}", e);
+        }
+    }
+}
+
+fn get_threads(
+    conn: &mut SqliteConnection,
+    todo_keys: &[(i64, i32)],
+) -> QueryResult<Vec<ChannelId>> {
+    use crate::schema::todos::dsl::{channel_id, id, thread_id, todos};
+
+    let mut threads = vec![];
+    for &(channel, todo) in todo_keys {
+        let thread = todos
+            .filter(channel_id.eq(channel))
+            .filter(id.eq(todo))
+            .select(thread_id)
+            .first::<Option<i64>>(conn)
+            .optional()?
+            .flatten();
+        threads.extend(thread.map(|thread| ChannelId(thread as u64)));
+    }
+
+    Ok(threads)
+}
@@ -0,0 +1,745 @@
+use std::{borrow::Cow, fmt::Write, sync::LazyLock};
+
+use diesel::{
+    prelude::*,
+    result::{DatabaseErrorKind::UniqueViolation, Error::DatabaseError, QueryResult},
+};
+use itertools::Itertools;
+use poise::serenity_prelude::{Attachment, AttachmentType, MessageBuilder};
+use regex::Regex;
+use serde::Deserializer;
+use serde_derive::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::debug;
+
+use super::{
+    get_assignees, get_tags, limit_description, log_event, next_todo_id, normalize_tag,
+    parse_due_date, respond_text, set_assignees, Assignee, Priority, Recurrence, TodoAction,
+    ID_CONFLICT,
+};
+use crate::{
+    commands::{parse_time, TIME_FORMAT},
+    models::todo::{NewTag, NewTodo, Todo},
+    Context, Result,
+};
+
+// Largest file accepted by `/todo import`
+const MAX_IMPORT_SIZE: u64 = 1024 * 1024;
+// Most TODOs imported at once, all of them are inserted in one transaction
+const MAX_IMPORT_ROWS: usize = 1000;
+// Number of row errors listed in the import summary
+const MAX_REPORTED_ERRORS: usize = 20;
+
+const CSV_COLUMNS: [&str; 9] = [
+    "id",
+    "todo",
+    "creation_date",
+    "completion_date",
+    "assignees",
+    "priority",
+    "due_date",
+    "recurrence",
+    "tags",
+];
+// Columns of files exported before TODOs could have multiple assignees
+const LEGACY_CSV_COLUMNS: [&str; 1] = ["assignee"];
+
+static MARKDOWN_ITEM_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[-*] \[([ xX])\] (.+)$").unwrap());
+static MARKDOWN_FIELD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s+[-*] ([a-z_]+): (.*)$").unwrap());
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum Format {
+    #[name = "JSON"]
+    Json,
+    #[name = "CSV"]
+    Csv,
+    #[name = "Markdown"]
+    Markdown,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Csv => "csv",
+            Format::Markdown => "md",
+        }
+    }
+
+    fn from_filename(filename: &str) -> Option<Self> {
+        let (_, extension) = filename.rsplit_once('.')?;
+        match extension.to_lowercase().as_str() {
+            "json" => Some(Format::Json),
+            "csv" => Some(Format::Csv),
+            "md" | "markdown" => Some(Format::Markdown),
+            _ => None,
+        }
+    }
+}
+
+/// TODO as it's represented in the exported files.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Record {
+    id: Option<i32>,
+    todo: String,
+    creation_date: Option<String>,
+    completion_date: Option<String>,
+    #[serde(alias = "assignee", deserialize_with = "one_or_many")]
+    assignees: Vec<String>,
+    #[serde(deserialize_with = "string_or_number")]
+    priority: String,
+    due_date: Option<String>,
+    recurrence: Option<String>,
+    tags: Vec<String>,
+}
+
+impl Record {
+    fn new(todo: Todo, assignees: &[Assignee], tags: Vec<String>) -> Self {
+        Self {
+            id: Some(todo.id),
+            todo: todo.todo,
+            creation_date: Some(todo.creation_date),
+            completion_date: todo.completion_date,
+            // Members are exported as plain IDs, roles as their mentions
+            assignees: assignees
+                .iter()
+                .map(|assignee| match assignee {
+                    Assignee::Member(user) => user.to_string(),
+                    Assignee::Role(_) => assignee.mention().to_string(),
+                })
+                .collect(),
+            priority: Priority::from(todo.priority).to_string(),
+            due_date: todo.due_date,
+            recurrence: todo.recurrence,
+            tags,
+        }
+    }
+
+    fn set_field(&mut self, name: &str, value: &str) -> std::result::Result<(), String> {
+        let optional = || Some(value.to_string()).filter(|v| !v.is_empty());
+        match name {
+            "id" => self.id = value.parse().ok(),
+            "todo" => self.todo = value.to_string(),
+            "creation_date" | "created" => self.creation_date = optional(),
+            "completion_date" | "completed" => self.completion_date = optional(),
+            "assignees" | "assignee" => {
+                self.assignees = value.split_whitespace().map(String::from).collect();
+            }
+            "priority" => self.priority = value.to_string(),
+            "due_date" | "due" => self.due_date = optional(),
+            "recurrence" | "repeats" => self.recurrence = optional(),
+            "tags" => self.tags = value.split_whitespace().map(String::from).collect(),
+            _ => return Err(format!("unknown field `{name}`")),
+        }
+        Ok(())
+    }
+}
+
+/// Accepts a single value too, files exported before TODOs could have
+/// multiple assignees have just one.
+fn one_or_many<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(Option<String>),
+        Many(Vec<String>),
+    }
+
+    Ok(match serde::Deserialize::deserialize(deserializer)? {
+        OneOrMany::One(one) => one.into_iter().collect(),
+        OneOrMany::Many(many) => many,
+    })
+}
+
+/// Accepts a number too, priority can be given by its numeric value in
+/// edited files.
+fn string_or_number<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Number(i64),
+    }
+
+    Ok(match serde::Deserialize::deserialize(deserializer)? {
+        StringOrNumber::String(string) => string,
+        StringOrNumber::Number(number) => number.to_string(),
+    })
+}
+
+/// Validated TODO ready to be inserted.
+struct ImportedTodo {
+    text: String,
+    creation_date: String,
+    completion_date: Option<String>,
+    assignees: Vec<Assignee>,
+    priority: i32,
+    due_date: Option<String>,
+    recurrence: Option<String>,
+    tags: Vec<String>,
+}
+
+impl TryFrom<Record> for ImportedTodo {
+    type Error = String;
+
+    fn try_from(record: Record) -> std::result::Result<Self, Self::Error> {
+        let now = OffsetDateTime::now_utc().format(&TIME_FORMAT).unwrap();
+
+        let text = record.todo.trim().to_string();
+        if text.is_empty() {
+            return Err("content can't be empty".to_string());
+        }
+        if text.len() > 1024 {
+            return Err("content can't have more than 1024 characters".to_string());
+        }
+
+        let priority = parse_priority(&record.priority)
+            .ok_or_else(|| format!("invalid priority `{}`", record.priority))?;
+
+        let assignees = record
+            .assignees
+            .iter()
+            .map(|assignee| {
+                assignee
+                    .trim()
+                    .parse::<Assignee>()
+                    .map_err(|_| format!("invalid assignee `{assignee}`"))
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?
+            .into_iter()
+            .unique()
+            .collect();
+
+        let parse_date = |date: Option<String>, name: &str| match date {
+            Some(date) => parse_time(&date)
+                .map(|date| date.format(&TIME_FORMAT).unwrap())
+                .or_else(|| parse_due_date(&date))
+                .map(Some)
+                .ok_or_else(|| format!("invalid {name} `{date}`")),
+            None => Ok(None),
+        };
+
+        let creation_date = parse_date(record.creation_date, "creation date")?.unwrap_or(now);
+        let completion_date = parse_date(record.completion_date, "completion date")?;
+        let due_date = parse_date(record.due_date, "due date")?;
+
+        let recurrence = match record.recurrence {
+            Some(recurrence) => Some(
+                recurrence
+                    .parse::<Recurrence>()
+                    .map_err(|e| format!("invalid recurrence `{recurrence}`, {e}"))?
+                    .to_string(),
+            ),
+            None => None,
+        };
+
+        let tags = record
+            .tags
+            .iter()
+            .map(|tag| normalize_tag(tag))
+            .filter(|tag| !tag.is_empty())
+            .unique()
+            .collect();
+
+        Ok(Self {
+            text,
+            creation_date,
+            completion_date,
+            assignees,
+            priority,
+            due_date,
+            recurrence,
+            tags,
+        })
+    }
+}
+
+/// Accepts priority names case insensitively, their numeric values and empty
+/// value meaning no priority.
+fn parse_priority(input: &str) -> Option<i32> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Some(Priority::None as i32);
+    }
+
+    Priority::VALUES
+        .iter()
+        .position(|p| p.eq_ignore_ascii_case(input))
+        .map(|p| p as i32)
+        .or_else(|| input.parse().ok().filter(|p| (0..=3).contains(p)))
+}
+
+/// Export TODO entries of the channel to a file
+#[poise::command(slash_command)]
+pub async fn export(
+    ctx: Context<'_>,
+    #[description = "File format, JSON by default"] format: Option<Format>,
+) -> Result<()> {
+    use crate::schema::todos::dsl::{channel_id, deletion_date, id, todos};
+
+    let format = format.unwrap_or(Format::Json);
+    let channel = i64::from(ctx.channel_id());
+
+    let (results, assignees, tags) = {
+        let conn = &mut ctx.data().db.get().unwrap();
+        let results = todos
+            .filter(channel_id.eq(channel))
+            .filter(deletion_date.is_null())
+            .order(id.asc())
+            .load::<Todo>(conn);
+        (
+            results,
+            get_assignees(conn, &[channel]),
+            get_tags(conn, &[channel]),
+        )
+    };
+
+    let results = results.and_then(|todo_list| Ok((todo_list, assignees?, tags?)));
+    let Ok((todo_list, assignees, mut tags)) = results else {
+        respond_text(ctx, "Exporting TODOs failed.".to_string(), true).await;
+        return Ok(());
+    };
+
+    let records: Vec<Record> = todo_list
+        .into_iter()
+        .map(|todo| {
+            let key = (todo.channel_id, todo.id);
+            let todo_assignees = assignees.get(&key).map_or(&[][..], Vec::as_slice);
+            let todo_tags = tags.remove(&key).unwrap_or_default();
+            Record::new(todo, todo_assignees, todo_tags)
+        })
+        .collect();
+
+    let count = records.len();
+    let data = match format {
+        Format::Json => serde_json::to_string_pretty(&records)?,
+        Format::Csv => to_csv(&records),
+        Format::Markdown => to_markdown(&records),
+    };
+    let filename = format!("todos-{}.{}", ctx.channel_id(), format.extension());
+
+    let response = ctx
+        .send(|reply| {
+            reply
+                .embed(|embed| embed.description(format!("Exported {count} TODOs.")))
+                .attachment(AttachmentType::Bytes {
+                    data: Cow::Owned(data.into_bytes()),
+                    filename,
+                })
+                .ephemeral(true)
+        })
+        .await;
+
+    if let Err(e) = response {
+        debug!("{:?}", e);
+    }
+
+    Ok(())
+}
+
+/// Import TODO entries from a file
+#[poise::command(slash_command)]
+pub async fn import(
+    ctx: Context<'_>,
+    #[description = "JSON, CSV or Markdown file, e.g. made by `/todo export`"] file: Attachment,
+    #[description = "File format, detected from the file name by default"] format: Option<Format>,
+) -> Result<()> {
+    let Some(format) = format.or_else(|| Format::from_filename(&file.filename)) else {
+        respond_text(ctx, "Unknown file format.".to_string(), true).await;
+        return Ok(());
+    };
+
+    if file.size > MAX_IMPORT_SIZE {
+        respond_text(ctx, "File is too large.".to_string(), true).await;
+        return Ok(());
+    }
+
+    ctx.defer_ephemeral().await?;
+
+    let content = match file.download().await.map(String::from_utf8) {
+        Ok(Ok(content)) => content,
+        Ok(Err(_)) => {
+            respond_text(ctx, "File isn't valid UTF-8 text.".to_string(), true).await;
+            return Ok(());
+        }
+        Err(e) => {
+            debug!("{:?}", e);
+            respond_text(ctx, "Downloading file failed.".to_string(), true).await;
+            return Ok(());
+        }
+    };
+
+    let rows = match format {
+        Format::Json => from_json(&content),
+        Format::Csv => from_csv(&content),
+        Format::Markdown => Ok(from_markdown(&content)),
+    };
+
+    let rows = match rows {
+        Ok(rows) => rows,
+        Err(e) => {
+            respond_text(ctx, format!("Invalid file, {e}."), true).await;
+            return Ok(());
+        }
+    };
+    if rows.len() > MAX_IMPORT_ROWS {
+        let text = format!("File can't have more than {MAX_IMPORT_ROWS} TODOs.");
+        respond_text(ctx, text, true).await;
+        return Ok(());
+    }
+
+    let mut errors = vec![];
+    let mut valid = vec![];
+    for (row, record) in rows {
+        match record.and_then(ImportedTodo::try_from) {
+            Ok(todo) => valid.push(todo),
+            Err(e) => errors.push(format!("Row {row}: {e}.")),
+        }
+    }
+
+    let data = match insert_todos(ctx, &valid) {
+        Ok(ids) => {
+            if let Some(guild) = ctx.guild_id() {
+                for tag in valid.iter().flat_map(|todo| &todo.tags) {
+                    ctx.data().todo_data.add_tag(guild, tag.clone()).await;
+                }
+            }
+
+            let mut msg = MessageBuilder::new();
+            msg.push_line(format!("Imported {} TODOs.", ids.len()));
+            if let (Some(first), Some(last)) = (ids.first(), ids.last()) {
+                msg.push_line(format!("New TODOs have IDs from {first} to {last}."));
+            }
+            if !errors.is_empty() {
+                msg.push_line(format!("Skipped {} rows:", errors.len()));
+                for error in errors.iter().take(MAX_REPORTED_ERRORS) {
+                    msg.push_line_safe(error);
+                }
+                if errors.len() > MAX_REPORTED_ERRORS {
+                    msg.push_italic_line(format!(
+                        "and {} more.",
+                        errors.len() - MAX_REPORTED_ERRORS
+                    ));
+                }
+            }
+            // Errors can quote long values from the file
+            limit_description(msg.build())
+        }
+        Err(DatabaseError(UniqueViolation, _)) => ID_CONFLICT.to_string(),
+        Err(_) => "Importing TODOs failed.".to_string(),
+    };
+
+    respond_text(ctx, data, true).await;
+
+    Ok(())
+}
+
+/// Inserts all TODOs at once, returns their new IDs.
+fn insert_todos(ctx: Context<'_>, imported: &[ImportedTodo]) -> QueryResult<Vec<i32>> {
+    use crate::schema::{todo_tags::dsl::todo_tags, todos::dsl::todos};
+
+    let channel = i64::from(ctx.channel_id());
+    let guild = ctx.guild_id().map(i64::from);
+
+    ctx.data().db.get().unwrap().immediate_transaction(|conn| {
+        let mut ids = vec![];
+
+        for todo in imported {
+            let new_id = next_todo_id(conn, channel)?;
+
+            let new_todo = NewTodo {
+                channel_id: &channel,
+                id: &new_id,
+                todo: &todo.text,
+                creation_date: &todo.creation_date,
+                completion_date: todo.completion_date.clone(),
+                priority: todo.priority,
+                due_date: todo.due_date.clone(),
+                recurrence: todo.recurrence.clone(),
+                issue: None,
+                message_link: None,
+            };
+            diesel::insert_into(todos).values(&new_todo).execute(conn)?;
+            set_assignees(conn, channel, new_id, &todo.assignees)?;
+
+            if let Some(guild) = &guild {
+                for tag in &todo.tags {
+                    let new_tag = NewTag {
+                        channel_id: &channel,
+                        todo_id: &new_id,
+                        guild_id: guild,
+                        tag,
+                    };
+                    diesel::insert_or_ignore_into(todo_tags)
+                        .values(&new_tag)
+                        .execute(conn)?;
+                }
+            }
+
+            log_event(
+                conn,
+                ctx,
+                channel,
+                new_id,
+                TodoAction::Created,
+                None,
+                Some(&todo.text),
+            )?;
+
+            ids.push(new_id);
+        }
+
+        Ok(ids)
+    })
+}
+
+type Rows = Vec<(usize, std::result::Result<Record, String>)>;
+
+fn from_json(content: &str) -> std::result::Result<Rows, String> {
+    let values: Vec<serde_json::Value> =
+        serde_json::from_str(content).map_err(|e| format!("expected array of TODOs, {e}"))?;
+
+    Ok(values
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let record = serde_json::from_value(value).map_err(|e| e.to_string());
+            (i + 1, record)
+        })
+        .collect())
+}
+
+fn to_csv(records: &[Record]) -> String {
+    let mut writer = csv::WriterBuilder::new()
+        .terminator(csv::Terminator::CRLF)
+        .from_writer(vec![]);
+    // Writing to memory can't fail
+    writer.write_record(CSV_COLUMNS).unwrap();
+
+    for record in records {
+        let fields = [
+            record.id.map(|id| id.to_string()).unwrap_or_default(),
+            record.todo.clone(),
+            record.creation_date.clone().unwrap_or_default(),
+            record.completion_date.clone().unwrap_or_default(),
+            record.assignees.join(" "),
+            record.priority.clone(),
+            record.due_date.clone().unwrap_or_default(),
+            record.recurrence.clone().unwrap_or_default(),
+            record.tags.join(" "),
+        ];
+        writer.write_record(fields).unwrap();
+    }
+
+    String::from_utf8(writer.into_inner().unwrap()).unwrap()
+}
+
+fn from_csv(content: &str) -> std::result::Result<Rows, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let header: Vec<String> = reader
+        .headers()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+
+    if !header.iter().any(|h| h == "todo") {
+        return Err("missing `todo` column".to_string());
+    }
+
+    let mut rows = vec![];
+    for fields in reader.records() {
+        let fields = fields.map_err(|e| e.to_string())?;
+        if fields.iter().all(str::is_empty) {
+            continue;
+        }
+
+        // Line of the reader lags behind after CRLF, it's counted from the
+        // first byte of the record instead
+        let start = fields.position().map_or(0, |p| p.byte() as usize);
+        let start = content.len() - content[start..].trim_start_matches(['\r', '\n']).len();
+        let line = content[..start].matches('\n').count() + 1;
+        let mut record = Record::default();
+        let result = header
+            .iter()
+            .zip(&fields)
+            .filter(|(name, _)| {
+                CSV_COLUMNS.contains(&name.as_str()) || LEGACY_CSV_COLUMNS.contains(&name.as_str())
+            })
+            .try_for_each(|(name, value)| record.set_field(name, value));
+        rows.push((line, result.map(|()| record)));
+    }
+
+    Ok(rows)
+}
+
+fn to_markdown(records: &[Record]) -> String {
+    let mut markdown = String::from("# TODOs\n\n");
+
+    for record in records {
+        let mark = if record.completion_date.is_some() {
+            "x"
+        } else {
+            " "
+        };
+        _ = writeln!(markdown, "- [{mark}] {}", record.todo.replace('\n', " "));
+
+        let fields = [
+            (
+                "priority",
+                Some(record.priority.clone()).filter(|p| p != "None"),
+            ),
+            (
+                "assignees",
+                Some(record.assignees.join(" ")).filter(|a| !a.is_empty()),
+            ),
+            ("due", record.due_date.clone()),
+            ("repeats", record.recurrence.clone()),
+            (
+                "tags",
+                Some(record.tags.join(" ")).filter(|t| !t.is_empty()),
+            ),
+            ("created", record.creation_date.clone()),
+            ("completed", record.completion_date.clone()),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                _ = writeln!(markdown, "  - {name}: {value}");
+            }
+        }
+    }
+
+    markdown
+}
+
+/// Reads Markdown checklist, every item can be followed by nested list of
+/// `name: value` fields.
+fn from_markdown(content: &str) -> Rows {
+    let mut rows: Rows = vec![];
+
+    for (i, line) in content.lines().enumerate() {
+        if let Some(captures) = MARKDOWN_ITEM_REGEX.captures(line) {
+            let completed = &captures[1] != " ";
+            let record = Record {
+                todo: captures[2].to_string(),
+                completion_date: completed
+                    .then(|| OffsetDateTime::now_utc().format(&TIME_FORMAT).unwrap()),
+                ..Default::default()
+            };
+            rows.push((i + 1, Ok(record)));
+        } else if let Some(captures) = MARKDOWN_FIELD_REGEX.captures(line) {
+            if let Some((_, record)) = rows.last_mut() {
+                if let Ok(fields) = record {
+                    if let Err(e) = fields.set_field(&captures[1], captures[2].trim()) {
+                        *record = Err(e);
+                    }
+                }
+            }
+        }
+    }
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(todo: &str) -> Record {
+        Record {
+            id: Some(1),
+            todo: todo.to_string(),
+            creation_date: Some("2024-05-01 14:00:00".to_string()),
+            priority: "High".to_string(),
+            assignees: vec!["123".to_string(), "<@&456>".to_string()],
+            tags: vec!["bug".to_string(), "ui".to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn round_trip(records: &[Record]) -> Vec<Record> {
+        from_csv(&to_csv(records))
+            .unwrap()
+            .into_iter()
+            .map(|(_, record)| record.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn csv_round_trip_plain() {
+        let records = [record("Buy milk"), record("Walk the dog")];
+
+        assert_eq!(round_trip(&records), records);
+    }
+
+    #[test]
+    fn csv_round_trip_quotes() {
+        let records = [record("Say \"hello\""), record("\"quoted\" start")];
+
+        assert_eq!(round_trip(&records), records);
+    }
+
+    #[test]
+    fn csv_round_trip_commas_and_newlines() {
+        let records = [
+            record("Eggs, milk, bread"),
+            record("First line\nsecond line\r\nthird, \"line\""),
+        ];
+
+        assert_eq!(round_trip(&records), records);
+    }
+
+    #[test]
+    fn csv_lines_of_records() {
+        let csv = "todo,priority\r\n\"multi\nline\",High\r\nnext,Low\r\n";
+
+        let lines: Vec<usize> = from_csv(csv)
+            .unwrap()
+            .into_iter()
+            .map(|(line, _)| line)
+            .collect();
+        assert_eq!(lines, [2, 4]);
+
+        let lines: Vec<usize> = from_csv("todo\na\n\nb\n")
+            .unwrap()
+            .into_iter()
+            .map(|(line, _)| line)
+            .collect();
+        assert_eq!(lines, [2, 4]);
+    }
+
+    #[test]
+    fn csv_without_trailing_newline() {
+        let rows = from_csv("todo\nlast").unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].1.as_ref().unwrap().todo, "last");
+    }
+
+    #[test]
+    fn json_numeric_priority() {
+        let rows = from_json(r#"[{"todo": "a", "priority": 2}, {"todo": "b"}]"#).unwrap();
+        let records: Vec<Record> = rows.into_iter().map(|(_, r)| r.unwrap()).collect();
+
+        assert_eq!(records[0].priority, "2");
+        assert_eq!(parse_priority(&records[0].priority), Some(2));
+        assert_eq!(
+            parse_priority(&records[1].priority),
+            Some(Priority::None as i32)
+        );
+    }
+
+    #[test]
+    fn csv_missing_todo_column() {
+        assert!(from_csv("id,priority\n1,High\n").is_err());
+    }
+}
@@ -0,0 +1,257 @@
+use diesel::{prelude::*, result::QueryResult};
+use itertools::Itertools;
+use poise::serenity_prelude::{Http, MessageBuilder, UserId};
+use tracing::debug;
+
+use super::{
+    change_todos, changed_ids, format_changed, format_ids, limit_description, push_change,
+    respond_text, IdSelection,
+};
+use crate::{ctx_data::CtxData, models::todo::Event, Context, Result};
+
+// Number of changes listed in a single notification
+const MAX_NOTIFIED_CHANGES: usize = 20;
+
+/// Watch TODO entries, you get direct messages about their changes
+#[poise::command(slash_command)]
+pub async fn watch(
+    ctx: Context<'_>,
+    #[description = "TODO ids, e.g. `3,5,9-12`"] todo_ids: IdSelection,
+) -> Result<()> {
+    use crate::schema::{
+        todo_events,
+        todo_watchers::dsl::{
+            channel_id as watcher_channel_id, last_event_id, todo_id as watcher_todo_id,
+            todo_watchers, user_id,
+        },
+        todos::dsl::{channel_id, deletion_date, id, todo, todos},
+    };
+
+    let channel = i64::from(ctx.channel_id());
+    let user = ctx.author().id.0 as i64;
+
+    let watched = ctx.data().db.get()?.immediate_transaction(|conn| {
+        change_todos(conn, todo_ids.ids(), |conn, todo_id| {
+            let text = todos
+                .filter(channel_id.eq(channel))
+                .filter(id.eq(todo_id))
+                .filter(deletion_date.is_null())
+                .select(todo)
+                .first::<String>(conn)?;
+            // Changes made before watching aren't notified
+            let latest = todo_events::table
+                .filter(todo_events::channel_id.eq(channel))
+                .filter(todo_events::todo_id.eq(todo_id))
+                .select(diesel::dsl::max(todo_events::id))
+                .first::<Option<i32>>(conn)?
+                .unwrap_or(0);
+            diesel::insert_or_ignore_into(todo_watchers)
+                .values((
+                    watcher_channel_id.eq(channel),
+                    watcher_todo_id.eq(todo_id),
+                    user_id.eq(user),
+                    last_event_id.eq(latest),
+                ))
+                .execute(conn)?;
+            Ok(text)
+        })
+    });
+
+    let ids = changed_ids(&watched);
+    let data = format_changed(watched, "Watching TODO failed.", |changed| match changed {
+        [(todo_id, content)] => MessageBuilder::new()
+            .push(format!("You are watching TODO [{todo_id}] ("))
+            .push_mono_safe(content)
+            .push("), you get direct messages about its changes.")
+            .build(),
+        _ => format!(
+            "You are watching TODOs {}, you get direct messages about their changes.",
+            format_ids(&ids)
+        ),
+    });
+
+    respond_text(ctx, data, true).await;
+
+    Ok(())
+}
+
+/// Stop watching TODO entries
+#[poise::command(slash_command)]
+pub async fn unwatch(
+    ctx: Context<'_>,
+    #[description = "TODO ids, e.g. `3,5,9-12`"] todo_ids: IdSelection,
+) -> Result<()> {
+    use crate::schema::todo_watchers::dsl::{
+        channel_id, todo_id as watcher_todo_id, todo_watchers, user_id,
+    };
+
+    let channel = i64::from(ctx.channel_id());
+    let user = ctx.author().id.0 as i64;
+
+    let unwatched = ctx.data().db.get()?.immediate_transaction(|conn| {
+        change_todos(conn, todo_ids.ids(), |conn, todo_id| {
+            let removed = diesel::delete(todo_watchers)
+                .filter(channel_id.eq(channel))
+                .filter(watcher_todo_id.eq(todo_id))
+                .filter(user_id.eq(user))
+                .execute(conn)?;
+            if removed == 0 {
+                return Err(diesel::result::Error::NotFound);
+            }
+            Ok(())
+        })
+    });
+
+    let ids = changed_ids(&unwatched);
+    let data = format_changed(
+        unwatched,
+        "Unwatching TODO failed.",
+        |changed| match changed {
+            [(todo_id, ())] => format!("You are no longer watching TODO [{todo_id}]."),
+            _ => format!("You are no longer watching TODOs {}.", format_ids(&ids)),
+        },
+    );
+
+    respond_text(ctx, data, true).await;
+
+    Ok(())
+}
+
+/// Copies watchers of the TODO to another TODO in the channel, has to be
+/// called in a transaction.
+pub(super) fn copy_watchers(
+    conn: &mut SqliteConnection,
+    channel: i64,
+    from: i32,
+    to: i32,
+) -> QueryResult<()> {
+    use crate::schema::todo_watchers::dsl::{
+        channel_id, last_event_id, todo_id, todo_watchers, user_id,
+    };
+
+    let watchers = todo_watchers
+        .filter(channel_id.eq(channel))
+        .filter(todo_id.eq(from))
+        .select((user_id, last_event_id))
+        .load::<(i64, i32)>(conn)?;
+
+    let rows: Vec<_> = watchers
+        .into_iter()
+        .map(|(user, last_event)| {
+            (
+                channel_id.eq(channel),
+                todo_id.eq(to),
+                user_id.eq(user),
+                last_event_id.eq(last_event),
+            )
+        })
+        .collect();
+    diesel::insert_or_ignore_into(todo_watchers)
+        .values(&rows)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Sends direct message to watchers of TODOs about changes made since the
+/// last one, changes made by the watchers themselves are left out.
+pub async fn notify_watchers(ctx_data: &CtxData, http: &Http) -> Result<()> {
+    use crate::schema::{
+        todo_events,
+        todo_watchers::dsl::{channel_id, last_event_id, todo_id, todo_watchers, user_id},
+        todos,
+    };
+
+    let changes = todo_watchers
+        .inner_join(
+            todo_events::table.on(todo_events::channel_id
+                .eq(channel_id)
+                .and(todo_events::todo_id.eq(todo_id))
+                .and(todo_events::id.gt(last_event_id))),
+        )
+        .inner_join(todos::table.on(todos::channel_id.eq(channel_id).and(todos::id.eq(todo_id))))
+        .select((user_id, todos::todo, todo_events::all_columns))
+        .order(todo_events::id.asc())
+        .load::<(i64, String, Event)>(&mut ctx_data.db.get()?)?;
+
+    let by_watcher = changes
+        .into_iter()
+        .into_group_map_by(|(watcher, _, _)| *watcher);
+
+    for (watcher, changes) in by_watcher {
+        let others: Vec<&(i64, String, Event)> = changes
+            .iter()
+            .filter(|(_, _, event)| event.actor != watcher)
+            .collect();
+        if !others.is_empty() {
+            send_changes(http, watcher, &others).await;
+        }
+
+        // Changes are marked as notified even if sending failed, otherwise
+        // they would be retried forever when the watcher has DMs closed
+        let latest = changes
+            .iter()
+            .map(|(_, _, event)| ((event.channel_id, event.todo_id), event.id))
+            .into_grouping_map()
+            .max();
+        let conn = &mut ctx_data.db.get()?;
+        for ((channel, todo), latest) in latest {
+            diesel::update(todo_watchers)
+                .filter(channel_id.eq(channel))
+                .filter(todo_id.eq(todo))
+                .filter(user_id.eq(watcher))
+                .set(last_event_id.eq(latest))
+                .execute(conn)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn send_changes(http: &Http, watcher: i64, changes: &[&(i64, String, Event)]) {
+    let mut msg = MessageBuilder::new();
+    let mut previous = None;
+
+    for (_, text, event) in changes.iter().take(MAX_NOTIFIED_CHANGES) {
+        let key = (event.channel_id, event.todo_id);
+        if previous != Some(key) {
+            msg.push(format!("TODO [{}] (", event.todo_id))
+                .push_mono_safe(text)
+                .push_line(format!(") in <#{}>:", event.channel_id));
+            previous = Some(key);
+        }
+        msg.push("- ");
+        push_change(&mut msg, event);
+        msg.push_line("");
+    }
+    if changes.len() > MAX_NOTIFIED_CHANGES {
+        msg.push_italic_line(format!(
+            "and {} more changes.",
+            changes.len() - MAX_NOTIFIED_CHANGES
+        ));
+    }
+
+    let description = limit_description(msg.build());
+
+    let channel = match UserId(watcher as u64).create_dm_channel(http).await {
+        Ok(channel) => channel,
+        Err(e) => {
+            debug!("Error while creating DM channel: {:?}", e);
+            return;
+        }
+    };
+
+    let response = channel
+        .send_message(http, |message| {
+            message.embed(|embed| {
+                embed
+                    .title("Watched TODOs changed")
+                    .description(description)
+            })
+        })
+        .await;
+
+    if let Err(e) = response {
+        debug!("Error while sending watched TODO changes: {:?}", e);
+    }
+}
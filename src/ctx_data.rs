@@ -1,6 +1,7 @@
 use crate::{
     commands::{hall_of_fame::HofData, ping::PingData, todo::TodoData},
     settings::Settings,
+    tasks::Scheduler,
     Conn,
 };
 
@@ -11,6 +12,7 @@ pub struct CtxData {
     pub todo_data: TodoData,
     pub hof_data: HofData,
     pub settings: Settings,
+    pub scheduler: Scheduler,
 }
 
 impl CtxData {
@@ -23,6 +25,7 @@ impl CtxData {
             todo_data,
             hof_data,
             settings,
+            scheduler: Scheduler::new(),
         }
     }
 }
@@ -9,7 +9,7 @@ use poise::{
 };
 use tracing::{debug, info};
 
-use crate::{ctx_data::CtxData, settings::Feature, tasks, Error, Result};
+use crate::{commands::todo, ctx_data::CtxData, settings::Feature, Context, Error, Result};
 
 pub async fn on_error(error: poise::FrameworkError<'_, Arc<CtxData>, Error>) {
     // This is our custom error handler
@@ -28,6 +28,42 @@ pub async fn on_error(error: poise::FrameworkError<'_, Arc<CtxData>, Error>) {
     }
 }
 
+// Commands changing TODOs shown on the boards, with their subcommands
+const BOARD_COMMANDS: [&str; 16] = [
+    "todo add",
+    "todo complete",
+    "todo uncomplete",
+    "todo delete",
+    "todo restore",
+    "todo assign",
+    "todo move",
+    "todo copy",
+    "todo move-all",
+    "todo edit",
+    "todo set_priority",
+    "todo import",
+    "todo check",
+    "todo tag",
+    "todo blocker",
+    // Context menu command creating TODO from message
+    "todo_from_message",
+];
+
+pub async fn post_command(ctx: Context<'_>) {
+    // Boards are refreshed right away after changes instead of waiting for
+    // the task
+    let name = &ctx.command().qualified_name;
+    let changes_boards = BOARD_COMMANDS.iter().any(|command| {
+        name.strip_prefix(command)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+    });
+    if changes_boards {
+        if let Err(e) = todo::update_boards(ctx.data(), ctx.http()).await {
+            debug!("Updating TODO boards failed: {:?}", e);
+        }
+    }
+}
+
 pub async fn event_handler<'a>(
     ctx: &'a serenity::Context,
     event: &'a Event<'_>,
@@ -124,7 +160,7 @@ pub async fn setup<'a>(
     //     poise::builtins::register_in_guild(ctx, commands,
     // serenity::GuildId(guild.id)).await?; }
 
-    tasks::start_tasks(&ctx_data, ctx.http.clone());
+    ctx_data.scheduler.start(&ctx_data, ctx.http.clone()).await;
     Ok(ctx_data)
 }
 
@@ -4,7 +4,8 @@
 use std::sync::Arc;
 
 use diesel::{
-    r2d2::{ConnectionManager, Pool},
+    connection::SimpleConnection,
+    r2d2::{self, ConnectionManager, CustomizeConnection, Pool},
     sqlite::SqliteConnection,
 };
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
@@ -23,6 +24,7 @@ use crate::{
 #[allow(clippy::module_name_repetitions)]
 mod commands;
 
+mod chart;
 mod ctx_data;
 mod framework;
 mod models;
@@ -41,10 +43,23 @@ type Error = anyhow::Error;
 type Context<'a> = poise::Context<'a, Arc<CtxData>, Error>;
 type Conn = Pool<ConnectionManager<SqliteConnection>>;
 
+#[derive(Debug)]
+struct ConnectionOptions;
+
+impl CustomizeConnection<SqliteConnection, r2d2::Error> for ConnectionOptions {
+    fn on_acquire(&self, conn: &mut SqliteConnection) -> std::result::Result<(), r2d2::Error> {
+        // SQLite has foreign keys disabled by default, they are needed for cascades
+        conn.batch_execute("PRAGMA foreign_keys = ON;")
+            .map_err(r2d2::Error::QueryError)
+    }
+}
+
 fn setup_db(db_url: &String) -> Result<Conn> {
     let conn_man = ConnectionManager::<SqliteConnection>::new(db_url);
-    let pool =
-        Pool::new(conn_man).unwrap_or_else(|_| panic!("Error creating pool for: {}", &db_url));
+    let pool = Pool::builder()
+        .connection_customizer(Box::new(ConnectionOptions))
+        .build(conn_man)
+        .unwrap_or_else(|_| panic!("Error creating pool for: {}", &db_url));
 
     debug!("Running database migrations");
     match &pool.get()?.run_pending_migrations(MIGRATIONS) {
@@ -89,21 +104,46 @@ async fn main() {
             changelog::version(),
             ping::ping(),
             todo::todo(),
+            todo::todo_from_message(),
             hall_of_fame::hof(),
         ],
         event_handler: |ctx, event, framework, data| {
             Box::pin(framework::event_handler(ctx, event, framework, data))
         },
         on_error: |error| Box::pin(framework::on_error(error)),
+        post_command: |ctx| Box::pin(framework::post_command(ctx)),
         ..Default::default()
     };
 
-    poise::Framework::builder()
+    let framework = poise::Framework::builder()
         .token(&settings.discord_token)
         .options(options)
         .intents(get_intents())
-        .setup(|ctx, ready, framework| Box::pin(framework::setup(ctx, ready, framework, ctx_data)))
-        .run()
+        .setup({
+            let ctx_data = ctx_data.clone();
+            |ctx, ready, framework| Box::pin(framework::setup(ctx, ready, framework, ctx_data))
+        })
+        .build()
         .await
-        .expect("Couldn't initialize bot")
+        .expect("Couldn't initialize bot");
+
+    let shard_manager = framework.shard_manager().clone();
+    tokio::spawn(async move {
+        wait_for_shutdown().await;
+        info!("Shutting down");
+        ctx_data.scheduler.shutdown().await;
+        shard_manager.lock().await.shutdown_all().await;
+    });
+
+    framework.start().await.expect("Couldn't run bot");
+}
+
+async fn wait_for_shutdown() {
+    let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+        .expect("Couldn't register SIGTERM handler");
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {},
+        _ = terminate.recv() => {},
+    }
 }
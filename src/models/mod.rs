@@ -1,2 +1,3 @@
 pub mod hall_of_fame;
+pub mod task;
 pub mod todo;
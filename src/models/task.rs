@@ -0,0 +1,12 @@
+use diesel::{AsChangeset, Insertable};
+
+use crate::schema::scheduled_tasks;
+
+#[derive(Insertable, AsChangeset)]
+#[diesel(table_name = scheduled_tasks, primary_key(name), treat_none_as_null = true)]
+pub struct NewTaskState<'a> {
+    pub name: &'a str,
+    pub last_run: Option<&'a str>,
+    pub next_run: Option<&'a str>,
+    pub last_error: Option<&'a str>,
+}
@@ -1,6 +1,11 @@
-use diesel::{Insertable, Queryable};
+use diesel::{
+    sql_types::{BigInt, Integer},
+    AsChangeset, Insertable, Queryable, QueryableByName,
+};
 
-use crate::schema::todos;
+use crate::schema::{
+    todo_channels, todo_checklist_items, todo_events, todo_reminders, todo_tags, todos,
+};
 
 #[derive(Queryable, Debug)]
 pub struct Todo {
@@ -10,8 +15,13 @@ pub struct Todo {
     pub todo: String,
     pub creation_date: String,
     pub completion_date: Option<String>,
-    pub assignee: Option<i64>,
     pub priority: i32,
+    pub due_date: Option<String>,
+    pub deletion_date: Option<String>,
+    pub recurrence: Option<String>,
+    pub issue: Option<i32>,
+    pub message_link: Option<String>,
+    pub thread_id: Option<i64>,
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -22,6 +32,123 @@ pub struct NewTodo<'a> {
     pub id: &'a i32,
     pub todo: &'a str,
     pub creation_date: &'a str,
-    pub assignee: Option<i64>,
+    pub completion_date: Option<String>,
     pub priority: i32,
+    pub due_date: Option<String>,
+    pub recurrence: Option<String>,
+    pub issue: Option<i32>,
+    pub message_link: Option<String>,
+}
+
+#[derive(Queryable, Debug)]
+pub struct Reminder {
+    pub id: i32,
+    pub channel_id: i64,
+    pub todo_id: i32,
+    pub guild_id: Option<i64>,
+    pub requester: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = todo_reminders)]
+pub struct NewReminder<'a> {
+    pub channel_id: &'a i64,
+    pub todo_id: &'a i32,
+    pub guild_id: Option<i64>,
+    pub requester: &'a i64,
+    pub remind_date: &'a str,
+    pub creation_date: &'a str,
+}
+
+#[derive(Queryable, Debug, Default)]
+pub struct ChannelSettings {
+    pub channel_id: i64,
+    pub reminders_enabled: Option<bool>,
+    pub reminder_interval: Option<i32>,
+    pub reminder_time: Option<String>,
+    pub reminder_quiet_days: Option<String>,
+    pub last_reminder_date: Option<String>,
+    pub create_threads: Option<bool>,
+}
+
+#[derive(Insertable, AsChangeset)]
+#[diesel(table_name = todo_channels, primary_key(channel_id))]
+pub struct NewChannelSettings<'a> {
+    pub channel_id: &'a i64,
+    pub reminders_enabled: Option<bool>,
+    pub reminder_interval: Option<i32>,
+    pub reminder_time: Option<String>,
+    pub reminder_quiet_days: Option<String>,
+    pub create_threads: Option<bool>,
+}
+
+#[derive(Queryable, Debug)]
+pub struct ChecklistItem {
+    pub id: i32,
+    pub text: String,
+    pub checked: bool,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = todo_checklist_items)]
+pub struct NewChecklistItem<'a> {
+    pub channel_id: &'a i64,
+    pub todo_id: &'a i32,
+    pub id: &'a i32,
+    pub text: &'a str,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = todo_tags)]
+pub struct NewTag<'a> {
+    pub channel_id: &'a i64,
+    pub todo_id: &'a i32,
+    pub guild_id: &'a i64,
+    pub tag: &'a str,
+}
+
+#[derive(Queryable, Debug)]
+pub struct Event {
+    pub id: i32,
+    pub channel_id: i64,
+    pub todo_id: i32,
+    pub actor: i64,
+    pub action: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub creation_date: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = todo_events)]
+pub struct NewEvent<'a> {
+    pub channel_id: &'a i64,
+    pub todo_id: &'a i32,
+    pub actor: &'a i64,
+    pub action: &'a str,
+    pub old_value: Option<&'a str>,
+    pub new_value: Option<&'a str>,
+    pub creation_date: &'a str,
+}
+
+#[derive(Queryable, Debug)]
+pub struct Board {
+    pub channel_id: i64,
+    pub message_id: i64,
+    pub last_event_id: Option<i32>,
+}
+
+#[derive(Queryable, Debug)]
+pub struct GithubRepo {
+    pub channel_id: i64,
+    pub repository: String,
+    pub last_sync_date: Option<String>,
+}
+
+#[derive(QueryableByName, Debug)]
+pub struct SearchMatch {
+    #[diesel(sql_type = BigInt)]
+    pub channel_id: i64,
+    #[diesel(sql_type = Integer)]
+    pub todo_id: i32,
 }
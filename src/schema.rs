@@ -20,6 +20,121 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    scheduled_tasks (name) {
+        name -> Text,
+        last_run -> Nullable<Text>,
+        next_run -> Nullable<Text>,
+        last_error -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    todo_assignees (channel_id, todo_id, assignee_id) {
+        channel_id -> BigInt,
+        todo_id -> Integer,
+        assignee_id -> BigInt,
+        is_role -> Bool,
+    }
+}
+
+diesel::table! {
+    todo_boards (channel_id) {
+        channel_id -> BigInt,
+        message_id -> BigInt,
+        last_event_id -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    todo_channels (channel_id) {
+        channel_id -> BigInt,
+        reminders_enabled -> Nullable<Bool>,
+        reminder_interval -> Nullable<Integer>,
+        reminder_time -> Nullable<Text>,
+        reminder_quiet_days -> Nullable<Text>,
+        last_reminder_date -> Nullable<Text>,
+        create_threads -> Nullable<Bool>,
+    }
+}
+
+diesel::table! {
+    todo_checklist_items (channel_id, todo_id, id) {
+        channel_id -> BigInt,
+        todo_id -> Integer,
+        id -> Integer,
+        text -> Text,
+        checked -> Bool,
+    }
+}
+
+diesel::table! {
+    todo_dependencies (channel_id, todo_id, blocker_channel_id, blocker_id) {
+        channel_id -> BigInt,
+        todo_id -> Integer,
+        blocker_channel_id -> BigInt,
+        blocker_id -> Integer,
+    }
+}
+
+diesel::table! {
+    todo_events (id) {
+        id -> Integer,
+        channel_id -> BigInt,
+        todo_id -> Integer,
+        actor -> BigInt,
+        action -> Text,
+        old_value -> Nullable<Text>,
+        new_value -> Nullable<Text>,
+        creation_date -> Text,
+    }
+}
+
+diesel::table! {
+    todo_github_repos (channel_id) {
+        channel_id -> BigInt,
+        repository -> Text,
+        last_sync_date -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    todo_reminders (id) {
+        id -> Integer,
+        channel_id -> BigInt,
+        todo_id -> Integer,
+        guild_id -> Nullable<BigInt>,
+        requester -> BigInt,
+        remind_date -> Text,
+        creation_date -> Text,
+    }
+}
+
+diesel::table! {
+    todo_sequences (channel_id) {
+        channel_id -> BigInt,
+        next_id -> Integer,
+    }
+}
+
+diesel::table! {
+    todo_tags (channel_id, todo_id, tag) {
+        channel_id -> BigInt,
+        todo_id -> Integer,
+        guild_id -> BigInt,
+        tag -> Text,
+    }
+}
+
+diesel::table! {
+    todo_watchers (channel_id, todo_id, user_id) {
+        channel_id -> BigInt,
+        todo_id -> Integer,
+        user_id -> BigInt,
+        last_event_id -> Integer,
+    }
+}
+
 diesel::table! {
     todos (channel_id, id) {
         channel_id -> BigInt,
@@ -27,11 +142,32 @@ diesel::table! {
         todo -> Text,
         creation_date -> Text,
         completion_date -> Nullable<Text>,
-        assignee -> Nullable<BigInt>,
         priority -> Integer,
+        due_date -> Nullable<Text>,
+        deletion_date -> Nullable<Text>,
+        recurrence -> Nullable<Text>,
+        issue -> Nullable<Integer>,
+        message_link -> Nullable<Text>,
+        thread_id -> Nullable<BigInt>,
     }
 }
 
 diesel::joinable!(hall_of_fame_entries -> hall_of_fame_tables (hof_id));
 
-diesel::allow_tables_to_appear_in_same_query!(hall_of_fame_entries, hall_of_fame_tables, todos,);
+diesel::allow_tables_to_appear_in_same_query!(
+    hall_of_fame_entries,
+    hall_of_fame_tables,
+    scheduled_tasks,
+    todo_assignees,
+    todo_boards,
+    todo_channels,
+    todo_checklist_items,
+    todo_dependencies,
+    todo_events,
+    todo_github_repos,
+    todo_reminders,
+    todo_sequences,
+    todo_tags,
+    todo_watchers,
+    todos,
+);
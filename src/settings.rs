@@ -6,18 +6,27 @@ use std::{
 
 use config::{Config, ConfigError, Environment, File};
 use glob::glob;
+use octocrab::Octocrab;
 use poise::serenity_prelude::{CacheHttp, Channel, ChannelId, GuildId};
 use serde_derive::Deserialize;
+use time::{Time, Weekday};
 use tracing::debug;
 
+use crate::tasks::{cron::Schedule, CatchUp};
+
+time::serde::format_description!(hour_minute, Time, "[hour]:[minute]");
+
 #[derive(Debug, Deserialize, Clone, PartialEq, Eq, Hash)]
 pub enum Feature {
     NotifyOnDeletedMessages,
     PeriodicTodoReminders,
+    WeeklyTodoDigest,
 }
 
 impl Feature {
-    fn all() -> HashSet<Self> {
+    /// Features enabled when none are configured, the weekly digest posts to
+    /// every channel with TODOs so guilds have to opt in.
+    fn defaults() -> HashSet<Self> {
         HashSet::from([
             Feature::NotifyOnDeletedMessages,
             Feature::PeriodicTodoReminders,
@@ -39,21 +48,167 @@ impl Default for Database {
     }
 }
 
+#[derive(Debug, Deserialize, Clone, Default)]
+#[allow(unused)]
+pub struct GitHub {
+    /// Base URL of the GitHub API, can point to a GitHub Enterprise instance
+    /// or a mock server
+    pub api_url: Option<String>,
+    /// Token used to open and close issues, synced repositories have to be
+    /// accessible with it
+    pub token: Option<String>,
+    /// Repositories channels can be linked to, as `owner/repo`, none if empty
+    #[serde(default)]
+    pub repositories: Vec<String>,
+}
+
+impl GitHub {
+    /// Whether channels can be linked to the repository, names are compared
+    /// case insensitively like GitHub does.
+    pub fn is_allowed(&self, repository: &str) -> bool {
+        self.repositories
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(repository))
+    }
+
+    pub fn client(&self) -> octocrab::Result<Octocrab> {
+        let mut builder = Octocrab::builder();
+        if let Some(url) = &self.api_url {
+            builder = builder.base_uri(url.as_str())?;
+        }
+        if let Some(token) = &self.token {
+            builder = builder.personal_token(token.clone());
+        }
+        builder.build()
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[allow(unused)]
+pub struct TodoReminders {
+    /// Number of days between reminders in a channel
+    #[serde(default = "TodoReminders::default_interval")]
+    pub interval: u32,
+    /// Time of day (UTC) after which reminders are sent, in `HH:MM` format
+    #[serde(default = "TodoReminders::default_time", with = "hour_minute")]
+    pub time: Time,
+    /// Days on which no reminders are sent
+    #[serde(default)]
+    pub quiet_days: HashSet<Weekday>,
+    /// Channels that opted out of reminders
+    #[serde(default)]
+    pub disabled_channels: HashSet<ChannelId>,
+}
+
+impl TodoReminders {
+    fn default_interval() -> u32 {
+        5
+    }
+
+    fn default_time() -> Time {
+        Time::from_hms(9, 0, 0).unwrap()
+    }
+}
+
+impl Default for TodoReminders {
+    fn default() -> Self {
+        Self {
+            interval: TodoReminders::default_interval(),
+            time: TodoReminders::default_time(),
+            quiet_days: HashSet::new(),
+            disabled_channels: HashSet::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[allow(unused)]
+pub struct TodoDigest {
+    /// Number of TODOs listed in each section of the digest
+    #[serde(default = "TodoDigest::default_listed")]
+    pub listed: usize,
+    /// Channels that opted out of the digest
+    #[serde(default)]
+    pub disabled_channels: HashSet<ChannelId>,
+}
+
+impl TodoDigest {
+    fn default_listed() -> usize {
+        5
+    }
+}
+
+impl Default for TodoDigest {
+    fn default() -> Self {
+        Self {
+            listed: TodoDigest::default_listed(),
+            disabled_channels: HashSet::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[allow(unused)]
+pub struct Todos {
+    /// Complete TODO once all its checklist items are checked
+    #[serde(default = "Todos::default_complete_with_checklist")]
+    pub complete_with_checklist: bool,
+    /// Number of days deleted TODOs are kept in the trash before being purged
+    #[serde(default = "Todos::default_trash_retention")]
+    pub trash_retention: u32,
+}
+
+impl Todos {
+    fn default_complete_with_checklist() -> bool {
+        true
+    }
+
+    fn default_trash_retention() -> u32 {
+        30
+    }
+}
+
+impl Default for Todos {
+    fn default() -> Self {
+        Self {
+            complete_with_checklist: Todos::default_complete_with_checklist(),
+            trash_retention: Todos::default_trash_retention(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[allow(unused)]
 pub struct BotSettings {
-    #[serde(default = "Feature::all")]
+    #[serde(default = "Feature::defaults")]
     pub features: HashSet<Feature>,
+    #[serde(default)]
+    pub todos: Todos,
+    #[serde(default)]
+    pub todo_reminders: TodoReminders,
+    #[serde(default)]
+    pub todo_digest: TodoDigest,
 }
 
 impl Default for BotSettings {
     fn default() -> Self {
         Self {
-            features: Feature::all(),
+            features: Feature::defaults(),
+            todos: Todos::default(),
+            todo_reminders: TodoReminders::default(),
+            todo_digest: TodoDigest::default(),
         }
     }
 }
 
+#[derive(Debug, Deserialize, Clone, Default)]
+#[allow(unused)]
+pub struct TaskSettings {
+    /// Cron expression overriding the default schedule of the task
+    pub schedule: Option<Schedule>,
+    pub catch_up: Option<CatchUp>,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[allow(unused)]
 pub struct Settings {
@@ -62,9 +217,14 @@ pub struct Settings {
     #[serde(default)]
     pub database: Database,
     #[serde(default)]
+    pub github: GitHub,
+    #[serde(default)]
     pub global: BotSettings,
     #[serde(default)]
     pub guilds: HashMap<GuildId, BotSettings>,
+    /// Scheduled tasks settings keyed by the task name
+    #[serde(default)]
+    pub tasks: HashMap<String, TaskSettings>,
 }
 
 impl Settings {
@@ -100,15 +260,29 @@ impl Settings {
         cache_http: impl CacheHttp,
         channel_id: &ChannelId,
     ) -> bool {
+        self.get_bot_settings(cache_http, channel_id)
+            .await
+            .features
+            .contains(feature)
+    }
+
+    /// Returns settings of the guild the channel belongs to, or the global
+    /// ones if there are none.
+    pub async fn get_bot_settings(
+        &self,
+        cache_http: impl CacheHttp,
+        channel_id: &ChannelId,
+    ) -> &BotSettings {
+        if self.guilds.is_empty() {
+            return &self.global;
+        }
+
         let channel = channel_id.to_channel(&cache_http).await.ok();
-        let default = self.global.features.contains(feature);
 
         if let Some(Channel::Guild(guild)) = channel {
-            self.guilds
-                .get(&guild.guild_id)
-                .map_or(default, |guild| guild.features.contains(feature))
+            self.guilds.get(&guild.guild_id).unwrap_or(&self.global)
         } else {
-            default
+            &self.global
         }
     }
 }
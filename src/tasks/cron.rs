@@ -0,0 +1,376 @@
+use std::{fmt, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer};
+use time::{Date, OffsetDateTime, Time};
+
+// How far into the future next occurrence is looked for
+const MAX_SEARCH_DAYS: usize = 366 * 5;
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+#[derive(Debug)]
+pub struct ParseError(String);
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid cron expression: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Set of allowed values of a single cron field, stored as a bitmask.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Field {
+    values: u64,
+    // Whether the field was anything else than `*`, needed for days matching
+    restricted: bool,
+}
+
+impl Field {
+    fn parse(input: &str, min: u8, max: u8, names: &[&str]) -> Result<Self, ParseError> {
+        let mut values = 0u64;
+
+        for part in input.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => {
+                    let step: u8 = step
+                        .parse()
+                        .map_err(|_| ParseError(format!("invalid step `{step}`")))?;
+                    if step == 0 {
+                        return Err(ParseError("step can't be zero".to_string()));
+                    }
+                    (range, step)
+                }
+                None => (part, 1),
+            };
+
+            let (start, end) = if range == "*" {
+                (min, max)
+            } else if let Some((start, end)) = range.split_once('-') {
+                (
+                    Self::parse_value(start, min, names)?,
+                    Self::parse_value(end, min, names)?,
+                )
+            } else {
+                let start = Self::parse_value(range, min, names)?;
+                // `5/15` means every 15 starting from 5
+                let end = if part.contains('/') { max } else { start };
+                (start, end)
+            };
+
+            if start < min || end > max || start > end {
+                return Err(ParseError(format!("value out of range in `{part}`")));
+            }
+
+            for value in (start..=end).step_by(step.into()) {
+                values |= 1 << value;
+            }
+        }
+
+        Ok(Self {
+            values,
+            restricted: !input.starts_with('*'),
+        })
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn parse_value(input: &str, min: u8, names: &[&str]) -> Result<u8, ParseError> {
+        let input = input.to_lowercase();
+        if let Some(pos) = names.iter().position(|&name| name == input) {
+            return Ok(pos as u8 + min);
+        }
+        input
+            .parse()
+            .map_err(|_| ParseError(format!("invalid value `{input}`")))
+    }
+
+    fn contains(self, value: u8) -> bool {
+        self.values & (1 << value) != 0
+    }
+}
+
+/// Cron schedule in the standard five field format (`minute hour
+/// day-of-month month day-of-week`), evaluated in UTC.
+///
+/// Fields support `*`, values, ranges, lists and steps, months and weekdays
+/// can be given by their three letter names. `@hourly`, `@daily`, `@weekly`,
+/// `@monthly` and `@yearly` shortcuts are supported as well.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    expression: String,
+    minutes: Field,
+    hours: Field,
+    days: Field,
+    months: Field,
+    weekdays: Field,
+}
+
+impl Schedule {
+    /// Returns the first occurrence strictly after `after`.
+    pub fn next_after(&self, after: OffsetDateTime) -> Option<OffsetDateTime> {
+        let after = after.to_offset(time::UtcOffset::UTC);
+        let start =
+            after.replace_second(0).ok()?.replace_nanosecond(0).ok()? + time::Duration::minutes(1);
+
+        let mut date = start.date();
+        let mut from = (start.hour(), start.minute());
+
+        for _ in 0..MAX_SEARCH_DAYS {
+            if self.matches_date(date) {
+                if let Some(time) = self.first_time_from(from) {
+                    return Some(date.with_time(time).assume_utc());
+                }
+            }
+            date = date.next_day()?;
+            from = (0, 0);
+        }
+
+        None
+    }
+
+    fn matches_date(&self, date: Date) -> bool {
+        if !self.months.contains(date.month().into()) {
+            return false;
+        }
+
+        let day = self.days.contains(date.day());
+        let weekday = self
+            .weekdays
+            .contains(date.weekday().number_days_from_sunday());
+
+        // Standard cron behaviour, if both are restricted either can match
+        match (self.days.restricted, self.weekdays.restricted) {
+            (true, true) => day || weekday,
+            (true, false) => day,
+            (false, true) => weekday,
+            (false, false) => true,
+        }
+    }
+
+    fn first_time_from(&self, (hour, minute): (u8, u8)) -> Option<Time> {
+        (hour..24)
+            .filter(|&h| self.hours.contains(h))
+            .find_map(|h| {
+                let from = if h == hour { minute } else { 0 };
+                (from..60)
+                    .find(|&m| self.minutes.contains(m))
+                    .and_then(|m| Time::from_hms(h, m, 0).ok())
+            })
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expression = match s.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * sun",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" => "0 0 1 1 *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(ParseError(format!(
+                "expected 5 fields, got {}",
+                fields.len()
+            )));
+        };
+
+        let mut weekdays = Field::parse(weekdays, 0, 7, &WEEKDAY_NAMES)?;
+        // Both 0 and 7 mean Sunday
+        if weekdays.contains(7) {
+            weekdays.values |= 1;
+        }
+
+        Ok(Self {
+            expression: s.trim().to_string(),
+            minutes: Field::parse(minutes, 0, 59, &[])?,
+            hours: Field::parse(hours, 0, 23, &[])?,
+            days: Field::parse(days, 1, 31, &[])?,
+            months: Field::parse(months, 1, 12, &MONTH_NAMES)?,
+            weekdays,
+        })
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+impl<'de> Deserialize<'de> for Schedule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let expression = String::deserialize(deserializer)?;
+        expression.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    fn next(expression: &str, after: OffsetDateTime) -> Option<OffsetDateTime> {
+        expression.parse::<Schedule>().unwrap().next_after(after)
+    }
+
+    #[test]
+    fn parse_values_ranges_and_steps() {
+        let schedule: Schedule = "0,30 9-17/4 */10 jan-mar mon-fri".parse().unwrap();
+
+        assert!(schedule.minutes.contains(0));
+        assert!(schedule.minutes.contains(30));
+        assert!(!schedule.minutes.contains(15));
+        assert_eq!(
+            (0..24)
+                .filter(|&h| schedule.hours.contains(h))
+                .collect::<Vec<_>>(),
+            [9, 13, 17]
+        );
+        assert_eq!(
+            (1..=31)
+                .filter(|&d| schedule.days.contains(d))
+                .collect::<Vec<_>>(),
+            [1, 11, 21, 31]
+        );
+        assert_eq!(
+            (1..=12)
+                .filter(|&m| schedule.months.contains(m))
+                .collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        assert_eq!(
+            (0..7)
+                .filter(|&d| schedule.weekdays.contains(d))
+                .collect::<Vec<_>>(),
+            [1, 2, 3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn parse_step_from_value() {
+        let schedule: Schedule = "5/20 * * * *".parse().unwrap();
+
+        assert_eq!(
+            (0..60)
+                .filter(|&m| schedule.minutes.contains(m))
+                .collect::<Vec<_>>(),
+            [5, 25, 45]
+        );
+    }
+
+    #[test]
+    fn parse_sunday_as_seven() {
+        let schedule: Schedule = "0 0 * * 7".parse().unwrap();
+
+        assert!(schedule.weekdays.contains(0));
+        assert!((1..7).all(|d| !schedule.weekdays.contains(d)));
+    }
+
+    #[test]
+    fn parse_shortcuts() {
+        assert_eq!(
+            "@weekly".parse::<Schedule>().unwrap().weekdays,
+            "0 0 * * sun".parse::<Schedule>().unwrap().weekdays
+        );
+        assert_eq!("@daily".parse::<Schedule>().unwrap().to_string(), "@daily");
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for expression in [
+            "",
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "* * * foo *",
+            "x * * * *",
+        ] {
+            assert!(expression.parse::<Schedule>().is_err(), "{expression}");
+        }
+    }
+
+    #[test]
+    fn next_after_is_strictly_later() {
+        assert_eq!(
+            next("30 9 * * *", datetime!(2024-05-01 9:30 UTC)),
+            Some(datetime!(2024-05-02 9:30 UTC))
+        );
+        assert_eq!(
+            next("30 9 * * *", datetime!(2024-05-01 9:29:59 UTC)),
+            Some(datetime!(2024-05-01 9:30 UTC))
+        );
+        assert_eq!(
+            next("* * * * *", datetime!(2024-05-01 23:59:30 UTC)),
+            Some(datetime!(2024-05-02 0:00 UTC))
+        );
+    }
+
+    #[test]
+    fn next_after_weekday() {
+        // 2024-05-01 is a Wednesday
+        assert_eq!(
+            next("0 9 * * mon", datetime!(2024-05-01 12:00 UTC)),
+            Some(datetime!(2024-05-06 9:00 UTC))
+        );
+        assert_eq!(
+            next("0 9 * * 7", datetime!(2024-05-01 12:00 UTC)),
+            Some(datetime!(2024-05-05 9:00 UTC))
+        );
+    }
+
+    #[test]
+    fn next_after_days_or_weekdays() {
+        // Either the 15th or a Monday matches when both are restricted
+        assert_eq!(
+            next("0 0 15 * mon", datetime!(2024-05-01 12:00 UTC)),
+            Some(datetime!(2024-05-06 0:00 UTC))
+        );
+        assert_eq!(
+            next("0 0 15 * mon", datetime!(2024-05-13 12:00 UTC)),
+            Some(datetime!(2024-05-15 0:00 UTC))
+        );
+        // Unrestricted weekdays don't widen the days
+        assert_eq!(
+            next("0 0 15 * *", datetime!(2024-05-01 12:00 UTC)),
+            Some(datetime!(2024-05-15 0:00 UTC))
+        );
+    }
+
+    #[test]
+    fn next_after_month_rollover() {
+        assert_eq!(
+            next("0 0 31 * *", datetime!(2024-04-01 0:00 UTC)),
+            Some(datetime!(2024-05-31 0:00 UTC))
+        );
+        assert_eq!(
+            next("@monthly", datetime!(2024-12-15 0:00 UTC)),
+            Some(datetime!(2025-01-01 0:00 UTC))
+        );
+        assert_eq!(
+            next("0 0 29 feb *", datetime!(2024-03-01 0:00 UTC)),
+            Some(datetime!(2028-02-29 0:00 UTC))
+        );
+    }
+
+    #[test]
+    fn next_after_never() {
+        assert_eq!(next("0 0 31 feb *", datetime!(2024-01-01 0:00 UTC)), None);
+    }
+}
@@ -1,32 +1,67 @@
 use std::sync::Arc;
 
 use poise::serenity_prelude::{async_trait, Http};
-use tokio::time::{sleep, Duration};
+use serde_derive::Deserialize;
 
-use crate::{ctx_data::CtxData, tasks::todo_reminder::TodoReminderTask};
+pub use self::scheduler::Scheduler;
+use crate::{
+    ctx_data::CtxData,
+    settings::Feature,
+    tasks::{
+        cron::Schedule, todo_board::TodoBoardTask, todo_digest::TodoDigestTask,
+        todo_dm_reminder::TodoDmReminderTask, todo_github_sync::TodoGithubSyncTask,
+        todo_purge::TodoPurgeTask, todo_reminder::TodoReminderTask,
+        todo_watchers::TodoWatchersTask,
+    },
+    Result,
+};
 
+pub mod cron;
+mod scheduler;
+mod todo_board;
+mod todo_digest;
+mod todo_dm_reminder;
+mod todo_github_sync;
+mod todo_purge;
 mod todo_reminder;
+mod todo_watchers;
+
+/// Policy for runs missed while the bot was not running.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CatchUp {
+    /// Missed runs are skipped, the task waits for the next scheduled time.
+    #[default]
+    Skip,
+    /// The task runs once on startup if any run was missed.
+    Once,
+}
 
 #[async_trait]
 pub trait Task: Send + Sync {
-    fn get_interval(&self) -> Duration;
-    async fn work(&self);
+    /// Unique name of the task, used to persist its state and to configure it.
+    fn name(&self) -> &'static str;
+    /// Default schedule of the task, can be overridden in the settings.
+    fn schedule(&self) -> Schedule;
+    /// Feature that has to be enabled globally or in any guild for the task
+    /// to run.
+    fn feature(&self) -> Option<Feature> {
+        None
+    }
+    fn catch_up(&self) -> CatchUp {
+        CatchUp::Skip
+    }
+    async fn work(&self) -> Result<()>;
 }
 
-// FIXME: the whole implementation works, but it less than ideal
-
 fn get_tasks(ctx_data: &Arc<CtxData>, http: Arc<Http>) -> Vec<Box<dyn Task>> {
-    let tasks: Vec<Box<dyn Task>> = vec![Box::new(TodoReminderTask::new(ctx_data.clone(), http))];
+    let tasks: Vec<Box<dyn Task>> = vec![
+        Box::new(TodoReminderTask::new(ctx_data.clone(), http.clone())),
+        Box::new(TodoDigestTask::new(ctx_data.clone(), http.clone())),
+        Box::new(TodoDmReminderTask::new(ctx_data.clone(), http.clone())),
+        Box::new(TodoPurgeTask::new(ctx_data.clone(), http.clone())),
+        Box::new(TodoGithubSyncTask::new(ctx_data.clone(), http.clone())),
+        Box::new(TodoBoardTask::new(ctx_data.clone(), http.clone())),
+        Box::new(TodoWatchersTask::new(ctx_data.clone(), http)),
+    ];
     tasks
 }
-
-pub fn start_tasks(ctx_data: &Arc<CtxData>, http: Arc<Http>) {
-    for task in get_tasks(ctx_data, http) {
-        tokio::task::spawn(async move {
-            loop {
-                task.work().await;
-                sleep(task.get_interval()).await;
-            }
-        });
-    }
-}
@@ -0,0 +1,235 @@
+use std::sync::Arc;
+
+use diesel::{prelude::*, result::QueryResult};
+use poise::serenity_prelude::Http;
+use rand::Rng;
+use time::OffsetDateTime;
+use tokio::{
+    sync::{watch, Mutex},
+    task::JoinHandle,
+    time::{sleep, Duration},
+};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    commands::{parse_time, TIME_FORMAT},
+    ctx_data::CtxData,
+    models::task::NewTaskState,
+    settings::{Feature, Settings},
+    tasks::{cron::Schedule, get_tasks, CatchUp, Task},
+    Conn,
+};
+
+// Random delay added to every run, so tasks scheduled at the same time don't
+// all hit the database and Discord at once
+const MAX_JITTER: Duration = Duration::from_secs(10);
+
+/// Runs [`Task`]s according to their cron schedules, persisting their state
+/// in the database.
+#[derive(Debug)]
+pub struct Scheduler {
+    shutdown: watch::Sender<bool>,
+    handles: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        let (shutdown, _) = watch::channel(false);
+        Self {
+            shutdown,
+            handles: Mutex::new(vec![]),
+        }
+    }
+
+    pub async fn start(&self, ctx_data: &Arc<CtxData>, http: Arc<Http>) {
+        let mut handles = self.handles.lock().await;
+        if !handles.is_empty() {
+            return;
+        }
+
+        for task in get_tasks(ctx_data, http) {
+            if let Some(runner) = TaskRunner::new(task, ctx_data, self.shutdown.subscribe()) {
+                handles.push(tokio::spawn(runner.run()));
+            }
+        }
+    }
+
+    /// Stops all tasks, waiting for the running ones to finish their work.
+    pub async fn shutdown(&self) {
+        _ = self.shutdown.send(true);
+
+        for handle in self.handles.lock().await.drain(..) {
+            if let Err(e) = handle.await {
+                debug!("Error while stopping task: {:?}", e);
+            }
+        }
+    }
+}
+
+struct TaskRunner {
+    task: Box<dyn Task>,
+    schedule: Schedule,
+    catch_up: CatchUp,
+    db: Conn,
+    shutdown: watch::Receiver<bool>,
+}
+
+impl TaskRunner {
+    fn new(
+        task: Box<dyn Task>,
+        ctx_data: &Arc<CtxData>,
+        shutdown: watch::Receiver<bool>,
+    ) -> Option<Self> {
+        let settings = &ctx_data.settings;
+
+        if let Some(feature) = task.feature() {
+            if !is_feature_enabled_anywhere(settings, &feature) {
+                info!("Task `{}` disabled by {:?} feature", task.name(), feature);
+                return None;
+            }
+        }
+
+        let task_settings = settings.tasks.get(task.name());
+        let schedule = task_settings
+            .and_then(|ts| ts.schedule.clone())
+            .unwrap_or_else(|| task.schedule());
+        let catch_up = task_settings
+            .and_then(|ts| ts.catch_up)
+            .unwrap_or_else(|| task.catch_up());
+
+        debug!("Task `{}` scheduled at `{}`", task.name(), schedule);
+
+        Some(Self {
+            task,
+            schedule,
+            catch_up,
+            db: ctx_data.db.clone(),
+            shutdown,
+        })
+    }
+
+    async fn run(mut self) {
+        let name = self.task.name();
+        let mut next = self.first_run();
+
+        while let Some(next_run) = next {
+            self.save_state(None, Some(next_run), None);
+
+            let jitter = rand::thread_rng().gen_range(Duration::ZERO..=MAX_JITTER);
+            let wait =
+                Duration::try_from(next_run - OffsetDateTime::now_utc()).unwrap_or(Duration::ZERO);
+
+            tokio::select! {
+                () = sleep(wait + jitter) => {}
+                _ = self.shutdown.changed() => break,
+            }
+
+            let started = OffsetDateTime::now_utc();
+            let error = match self.task.work().await {
+                Ok(()) => None,
+                Err(e) => {
+                    error!("Task `{name}` failed: {e:?}");
+                    Some(format!("{e:#}"))
+                }
+            };
+
+            next = self.schedule.next_after(OffsetDateTime::now_utc());
+            self.save_state(Some(started), next, error.as_deref());
+        }
+
+        if next.is_none() {
+            warn!("Task `{name}` has no next run in its schedule");
+        }
+        debug!("Task `{name}` stopped");
+    }
+
+    /// Computes the first run based on the last persisted run and catch-up
+    /// policy.
+    fn first_run(&self) -> Option<OffsetDateTime> {
+        let now = OffsetDateTime::now_utc();
+        let last_run = match self.load_last_run() {
+            Ok(last_run) => last_run.as_deref().and_then(parse_time),
+            Err(e) => {
+                debug!(
+                    "Error while loading state of `{}`: {:?}",
+                    self.task.name(),
+                    e
+                );
+                None
+            }
+        };
+
+        let missed = last_run
+            .and_then(|last| self.schedule.next_after(last))
+            .is_some_and(|next| next <= now);
+
+        if missed && self.catch_up == CatchUp::Once {
+            info!("Task `{}` missed its run, catching up", self.task.name());
+            Some(now)
+        } else {
+            self.schedule.next_after(now)
+        }
+    }
+
+    fn load_last_run(&self) -> QueryResult<Option<String>> {
+        use crate::schema::scheduled_tasks::dsl::{last_run, name, scheduled_tasks};
+
+        scheduled_tasks
+            .filter(name.eq(self.task.name()))
+            .select(last_run)
+            .first::<Option<String>>(&mut self.db.get().unwrap())
+            .optional()
+            .map(Option::flatten)
+    }
+
+    fn save_state(
+        &self,
+        last_run: Option<OffsetDateTime>,
+        next_run: Option<OffsetDateTime>,
+        last_error: Option<&str>,
+    ) {
+        use crate::schema::scheduled_tasks::dsl::{
+            name, next_run as next_run_col, scheduled_tasks,
+        };
+
+        let last_run = last_run.map(|t| t.format(&TIME_FORMAT).unwrap());
+        let next_run = next_run.map(|t| t.format(&TIME_FORMAT).unwrap());
+
+        let state = NewTaskState {
+            name: self.task.name(),
+            last_run: last_run.as_deref(),
+            next_run: next_run.as_deref(),
+            last_error,
+        };
+
+        let query = diesel::insert_into(scheduled_tasks)
+            .values(&state)
+            .on_conflict(name)
+            .do_update();
+
+        // Only finished runs update the last run and error
+        let result = if state.last_run.is_some() {
+            query.set(&state).execute(&mut self.db.get().unwrap())
+        } else {
+            query
+                .set(next_run_col.eq(state.next_run))
+                .execute(&mut self.db.get().unwrap())
+        };
+
+        if let Err(e) = result {
+            debug!(
+                "Error while saving state of `{}`: {:?}",
+                self.task.name(),
+                e
+            );
+        }
+    }
+}
+
+fn is_feature_enabled_anywhere(settings: &Settings, feature: &Feature) -> bool {
+    settings.global.features.contains(feature)
+        || settings
+            .guilds
+            .values()
+            .any(|guild| guild.features.contains(feature))
+}
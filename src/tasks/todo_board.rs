@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use poise::serenity_prelude::{async_trait, CacheHttp, Http};
+
+use crate::{
+    commands::todo,
+    ctx_data::CtxData,
+    tasks::{cron::Schedule, Task},
+    Result,
+};
+
+pub struct TodoBoardTask {
+    ctx_data: Arc<CtxData>,
+    http: Arc<dyn CacheHttp>,
+}
+
+impl TodoBoardTask {
+    pub fn new(ctx_data: Arc<CtxData>, http: Arc<Http>) -> Self {
+        Self { ctx_data, http }
+    }
+}
+
+#[async_trait]
+impl Task for TodoBoardTask {
+    fn name(&self) -> &'static str {
+        "todo_board"
+    }
+
+    fn schedule(&self) -> Schedule {
+        // Catches changes made outside of commands, e.g. by the GitHub sync
+        "* * * * *".parse().unwrap()
+    }
+
+    async fn work(&self) -> Result<()> {
+        todo::update_boards(&self.ctx_data, self.http.http()).await
+    }
+}
@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use poise::serenity_prelude::{async_trait, CacheHttp, Http};
+
+use crate::{
+    commands::todo,
+    ctx_data::CtxData,
+    settings::Feature,
+    tasks::{cron::Schedule, CatchUp, Task},
+    Result,
+};
+
+pub struct TodoDigestTask {
+    ctx_data: Arc<CtxData>,
+    http: Arc<dyn CacheHttp>,
+}
+
+impl TodoDigestTask {
+    pub fn new(ctx_data: Arc<CtxData>, http: Arc<Http>) -> Self {
+        Self { ctx_data, http }
+    }
+}
+
+#[async_trait]
+impl Task for TodoDigestTask {
+    fn name(&self) -> &'static str {
+        "todo_digest"
+    }
+
+    fn schedule(&self) -> Schedule {
+        // Mondays at 9:00 UTC
+        "0 9 * * mon".parse().unwrap()
+    }
+
+    fn feature(&self) -> Option<Feature> {
+        Some(Feature::WeeklyTodoDigest)
+    }
+
+    fn catch_up(&self) -> CatchUp {
+        // Missing a week would leave a gap in the digests
+        CatchUp::Once
+    }
+
+    async fn work(&self) -> Result<()> {
+        todo::send_digests(&self.ctx_data, self.http.http()).await
+    }
+}
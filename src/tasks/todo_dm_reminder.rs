@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use diesel::{BoolExpressionMethods, ExpressionMethods, JoinOnDsl, QueryDsl, RunQueryDsl};
+use poise::serenity_prelude::{async_trait, CacheHttp, Http, MessageBuilder, UserId};
+use time::OffsetDateTime;
+use tracing::debug;
+
+use crate::{
+    commands::TIME_FORMAT,
+    ctx_data::CtxData,
+    models::todo::{Reminder, Todo},
+    tasks::{cron::Schedule, Task},
+    Result,
+};
+
+pub struct TodoDmReminderTask {
+    ctx_data: Arc<CtxData>,
+    http: Arc<dyn CacheHttp>,
+}
+
+impl TodoDmReminderTask {
+    pub fn new(ctx_data: Arc<CtxData>, http: Arc<Http>) -> Self {
+        Self { ctx_data, http }
+    }
+
+    async fn send_reminder(&self, reminder: &Reminder, todo: &Todo, recipient: i64) {
+        #[allow(clippy::cast_sign_loss)]
+        let recipient = UserId(recipient as u64);
+        let guild = reminder
+            .guild_id
+            .map_or_else(|| "@me".to_string(), |guild| guild.to_string());
+        let link = format!(
+            "https://discord.com/channels/{guild}/{}",
+            reminder.channel_id
+        );
+
+        let description = MessageBuilder::new()
+            .push(format!("TODO [{}] (", reminder.todo_id))
+            .push_mono_safe(&todo.todo)
+            .push_line(format!(") in <#{}>.", reminder.channel_id))
+            .push(format!("[Jump to the channel]({link})"))
+            .build();
+
+        let channel = match recipient.create_dm_channel(self.http.http()).await {
+            Ok(channel) => channel,
+            Err(e) => {
+                debug!("Error while creating DM channel: {:?}", e);
+                return;
+            }
+        };
+
+        let response = channel
+            .send_message(self.http.http(), |message| {
+                message.embed(|embed| embed.title("TODO reminder").description(description))
+            })
+            .await;
+
+        if let Err(e) = response {
+            debug!("Error while sending reminder: {:?}", e);
+        }
+    }
+}
+
+#[async_trait]
+impl Task for TodoDmReminderTask {
+    fn name(&self) -> &'static str {
+        "todo_dm_reminders"
+    }
+
+    fn schedule(&self) -> Schedule {
+        // Due reminders are looked up in the database every minute
+        "* * * * *".parse().unwrap()
+    }
+
+    async fn work(&self) -> Result<()> {
+        use crate::schema::{
+            todo_assignees,
+            todo_reminders::dsl::{
+                channel_id, guild_id, id, remind_date, requester, todo_id, todo_reminders,
+            },
+            todos,
+        };
+
+        let now = OffsetDateTime::now_utc().format(&TIME_FORMAT).unwrap();
+
+        let results = todo_reminders
+            .inner_join(
+                todos::table.on(todos::channel_id.eq(channel_id).and(todos::id.eq(todo_id))),
+            )
+            .filter(remind_date.le(&now))
+            .select((
+                (id, channel_id, todo_id, guild_id, requester),
+                todos::all_columns,
+            ))
+            .load::<(Reminder, Todo)>(&mut self.ctx_data.db.get()?)?;
+
+        for (reminder, todo) in results {
+            if todo.completion_date.is_none() && todo.deletion_date.is_none() {
+                // Assigned members are reminded instead of the requester,
+                // members of assigned roles aren't known
+                let mut recipients = todo_assignees::table
+                    .filter(todo_assignees::channel_id.eq(todo.channel_id))
+                    .filter(todo_assignees::todo_id.eq(todo.id))
+                    .filter(todo_assignees::is_role.eq(false))
+                    .select(todo_assignees::assignee_id)
+                    .load::<i64>(&mut self.ctx_data.db.get()?)?;
+                if recipients.is_empty() {
+                    recipients.push(reminder.requester);
+                }
+                for recipient in recipients {
+                    self.send_reminder(&reminder, &todo, recipient).await;
+                }
+            }
+
+            diesel::delete(todo_reminders.filter(id.eq(reminder.id)))
+                .execute(&mut self.ctx_data.db.get()?)?;
+        }
+
+        Ok(())
+    }
+}
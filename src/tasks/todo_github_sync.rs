@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use poise::serenity_prelude::{async_trait, CacheHttp, Http};
+
+use crate::{
+    commands::todo,
+    ctx_data::CtxData,
+    tasks::{cron::Schedule, Task},
+    Result,
+};
+
+pub struct TodoGithubSyncTask {
+    ctx_data: Arc<CtxData>,
+    http: Arc<dyn CacheHttp>,
+}
+
+impl TodoGithubSyncTask {
+    pub fn new(ctx_data: Arc<CtxData>, http: Arc<Http>) -> Self {
+        Self { ctx_data, http }
+    }
+}
+
+#[async_trait]
+impl Task for TodoGithubSyncTask {
+    fn name(&self) -> &'static str {
+        "todo_github_sync"
+    }
+
+    fn schedule(&self) -> Schedule {
+        "*/15 * * * *".parse().unwrap()
+    }
+
+    async fn work(&self) -> Result<()> {
+        // Changes made by the sync are attributed to the bot
+        let bot = self.http.http().get_current_user().await?;
+        todo::sync_issues(&self.ctx_data, self.http.http(), bot.id).await
+    }
+}
@@ -0,0 +1,95 @@
+use std::{collections::HashMap, sync::Arc};
+
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use poise::serenity_prelude::{async_trait, CacheHttp, ChannelId, Http};
+use time::{Duration, OffsetDateTime};
+use tracing::debug;
+
+use crate::{
+    commands::parse_time,
+    ctx_data::CtxData,
+    tasks::{cron::Schedule, Task},
+    Result,
+};
+
+pub struct TodoPurgeTask {
+    ctx_data: Arc<CtxData>,
+    http: Arc<dyn CacheHttp>,
+}
+
+impl TodoPurgeTask {
+    pub fn new(ctx_data: Arc<CtxData>, http: Arc<Http>) -> Self {
+        Self { ctx_data, http }
+    }
+}
+
+#[async_trait]
+impl Task for TodoPurgeTask {
+    fn name(&self) -> &'static str {
+        "todo_purge"
+    }
+
+    fn schedule(&self) -> Schedule {
+        // Retention is counted in days, hourly checks are precise enough
+        "0 * * * *".parse().unwrap()
+    }
+
+    #[allow(clippy::cast_sign_loss)]
+    async fn work(&self) -> Result<()> {
+        use crate::schema::todos::dsl::{channel_id, deletion_date, id, todos};
+
+        let deleted = todos
+            .filter(deletion_date.is_not_null())
+            .select((channel_id, id, deletion_date))
+            .load::<(i64, i32, Option<String>)>(&mut self.ctx_data.db.get()?)?;
+
+        let mut channels: HashMap<i64, Vec<(i32, OffsetDateTime)>> = HashMap::new();
+        for (chnl, todo, date) in deleted {
+            if let Some(date) = date.as_deref().and_then(parse_time) {
+                channels.entry(chnl).or_default().push((todo, date));
+            }
+        }
+
+        let now = OffsetDateTime::now_utc();
+        let mut purged_any = false;
+
+        for (chnl, deleted) in channels {
+            let bot_settings = self
+                .ctx_data
+                .settings
+                .get_bot_settings(self.http.http(), &ChannelId(chnl as u64))
+                .await;
+            let retention = Duration::days(bot_settings.todos.trash_retention.into());
+
+            let expired: Vec<i32> = deleted
+                .into_iter()
+                .filter(|(_, date)| *date + retention <= now)
+                .map(|(todo, _)| todo)
+                .collect();
+
+            if expired.is_empty() {
+                continue;
+            }
+
+            let purged = diesel::delete(todos)
+                .filter(channel_id.eq(chnl))
+                .filter(id.eq_any(&expired))
+                .filter(deletion_date.is_not_null())
+                .execute(&mut self.ctx_data.db.get()?)?;
+
+            debug!("Purged {purged} deleted TODOs in channel {chnl}");
+            purged_any |= purged > 0;
+        }
+
+        // Tags of purged TODOs are deleted with them, so the autocomplete
+        // shouldn't offer them anymore
+        if purged_any {
+            self.ctx_data
+                .todo_data
+                .reload_tags(&self.ctx_data.db)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,11 +1,19 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
 use itertools::Itertools;
 use poise::serenity_prelude::{async_trait, CacheHttp, ChannelId, Http};
-use tokio::time::Duration;
+use time::OffsetDateTime;
+use tracing::debug;
 
-use crate::{ctx_data::CtxData, models::todo::Todo, tasks::Task};
+use crate::{
+    commands::{parse_time, todo::ReminderSchedule, TIME_FORMAT},
+    ctx_data::CtxData,
+    models::todo::{ChannelSettings, Todo},
+    settings::Feature,
+    tasks::{cron::Schedule, Task},
+    Result,
+};
 
 pub struct TodoReminderTask {
     ctx_data: Arc<CtxData>,
@@ -16,32 +24,93 @@ impl TodoReminderTask {
     pub fn new(ctx_data: Arc<CtxData>, http: Arc<Http>) -> Self {
         Self { ctx_data, http }
     }
+
+    fn set_last_reminder(&self, channel: ChannelId, date: &str) {
+        use crate::schema::todo_channels::dsl::{channel_id, last_reminder_date, todo_channels};
+
+        let result = diesel::insert_into(todo_channels)
+            .values((
+                channel_id.eq(i64::from(channel)),
+                last_reminder_date.eq(date),
+            ))
+            .on_conflict(channel_id)
+            .do_update()
+            .set(last_reminder_date.eq(date))
+            .execute(&mut self.ctx_data.db.get().unwrap());
+
+        if let Err(e) = result {
+            debug!("Error while saving last reminder date: {:?}", e);
+        }
+    }
 }
 
 #[async_trait]
 impl Task for TodoReminderTask {
-    fn get_interval(&self) -> Duration {
-        // Every 5 days
-        Duration::from_secs(60 * 60 * 24 * 5)
+    fn name(&self) -> &'static str {
+        "todo_reminders"
+    }
+
+    fn schedule(&self) -> Schedule {
+        // Channel schedules are checked every 10 minutes
+        "*/10 * * * *".parse().unwrap()
+    }
+
+    fn feature(&self) -> Option<Feature> {
+        Some(Feature::PeriodicTodoReminders)
     }
 
     #[allow(clippy::cast_sign_loss)]
-    async fn work(&self) {
-        use crate::schema::todos::dsl::{completion_date, todos};
+    async fn work(&self) -> Result<()> {
+        use crate::schema::{
+            todo_channels::dsl::todo_channels,
+            todos::dsl::{completion_date, deletion_date, todos},
+        };
 
         let results = todos
             .filter(completion_date.is_null())
+            .filter(deletion_date.is_null())
             .load::<Todo>(&mut self.ctx_data.db.get().unwrap());
 
-        let channels: Vec<(ChannelId, usize)> = results
-            .unwrap()
+        let channels: Vec<(ChannelId, usize)> = results?
             .into_iter()
             .group_by(|td| td.channel_id)
             .into_iter()
             .map(|(chnl, tds)| (ChannelId(chnl as u64), tds.count()))
             .collect();
 
+        let overrides: HashMap<ChannelId, ChannelSettings> = todo_channels
+            .load::<ChannelSettings>(&mut self.ctx_data.db.get()?)?
+            .into_iter()
+            .map(|cs| (ChannelId(cs.channel_id as u64), cs))
+            .collect();
+
+        let now = OffsetDateTime::now_utc();
+
         for (chnl, count) in channels {
+            let bot_settings = self
+                .ctx_data
+                .settings
+                .get_bot_settings(self.http.http(), &chnl)
+                .await;
+            if !bot_settings
+                .features
+                .contains(&Feature::PeriodicTodoReminders)
+            {
+                continue;
+            }
+
+            let overrides = overrides.get(&chnl);
+            let schedule = ReminderSchedule::new(&bot_settings.todo_reminders, chnl, overrides);
+            let last = overrides
+                .and_then(|o| o.last_reminder_date.as_deref())
+                .and_then(parse_time);
+
+            if !schedule.is_due(now, last) {
+                continue;
+            }
+
+            self.set_last_reminder(chnl, &now.format(&TIME_FORMAT).unwrap());
+
             _ = chnl
                 .send_message(&self.http.http(), |message| {
                     message.embed(|embed| {
@@ -52,5 +121,7 @@ impl Task for TodoReminderTask {
                 })
                 .await;
         }
+
+        Ok(())
     }
 }
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use poise::serenity_prelude::{async_trait, CacheHttp, Http};
+
+use crate::{
+    commands::todo,
+    ctx_data::CtxData,
+    tasks::{cron::Schedule, Task},
+    Result,
+};
+
+pub struct TodoWatchersTask {
+    ctx_data: Arc<CtxData>,
+    http: Arc<dyn CacheHttp>,
+}
+
+impl TodoWatchersTask {
+    pub fn new(ctx_data: Arc<CtxData>, http: Arc<Http>) -> Self {
+        Self { ctx_data, http }
+    }
+}
+
+#[async_trait]
+impl Task for TodoWatchersTask {
+    fn name(&self) -> &'static str {
+        "todo_watchers"
+    }
+
+    fn schedule(&self) -> Schedule {
+        // Changes made within a minute are sent together in one message
+        "* * * * *".parse().unwrap()
+    }
+
+    async fn work(&self) -> Result<()> {
+        todo::notify_watchers(&self.ctx_data, self.http.http()).await
+    }
+}
@@ -1,7 +1,29 @@
+use std::sync::LazyLock;
+
+use itertools::Itertools;
 use poise::serenity_prelude::{GuildId, Member, User, UserId};
+use regex::Regex;
+use time::{
+    format_description::{self, FormatItem},
+    Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time, Weekday,
+};
 
 use crate::{Context, Result};
 
+static RELATIVE_DATE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:in\s+)?(\d+)\s*(m|mins?|minutes?|h|hours?|d|days?|w|weeks?)$").unwrap()
+});
+static DATE_FORMAT: LazyLock<Vec<FormatItem<'static>>> =
+    LazyLock::new(|| format_description::parse("[year]-[month]-[day]").unwrap());
+static TIME_OF_DAY_FORMAT: LazyLock<Vec<FormatItem<'static>>> =
+    LazyLock::new(|| format_description::parse("[hour]:[minute]").unwrap());
+static DATETIME_FORMATS: LazyLock<[Vec<FormatItem<'static>>; 2]> = LazyLock::new(|| {
+    [
+        format_description::parse("[year]-[month]-[day] [hour]:[minute]:[second]").unwrap(),
+        format_description::parse("[year]-[month]-[day] [hour]:[minute]").unwrap(),
+    ]
+});
+
 pub async fn get_nick_from_id(
     ctx: Context<'_>,
     guild_id: &GuildId,
@@ -24,3 +46,215 @@ pub fn get_nick_from_member(member: &Member) -> String {
 
     member.user.name.clone()
 }
+
+/// Parses user provided date relative to `now`, all dates are in UTC.
+///
+/// Supported formats are `YYYY-MM-DD`, `YYYY-MM-DD HH:MM[:SS]`, `today`,
+/// `tomorrow` and relative forms like `in 3 days`, `2 weeks` or `30m`.
+pub fn parse_date(input: &str, now: OffsetDateTime) -> Option<OffsetDateTime> {
+    let input = input.trim().to_lowercase();
+
+    match input.as_str() {
+        "today" => return Some(now.replace_time(Time::MIDNIGHT)),
+        "tomorrow" => return Some(now.replace_time(Time::MIDNIGHT) + Duration::days(1)),
+        _ => {}
+    }
+
+    if let Some(caps) = RELATIVE_DATE_REGEX.captures(&input) {
+        let amount: i64 = caps[1].parse().ok()?;
+        // Duration constructors panic on overflow
+        let seconds = match &caps[2][..1] {
+            "m" => amount.checked_mul(60),
+            "h" => amount.checked_mul(3600),
+            "d" => amount.checked_mul(86_400),
+            "w" => amount.checked_mul(604_800),
+            _ => unreachable!(),
+        }?;
+        return now.checked_add(Duration::seconds(seconds));
+    }
+
+    if let Ok(date) = Date::parse(&input, &DATE_FORMAT) {
+        return Some(date.midnight().assume_utc());
+    }
+
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| PrimitiveDateTime::parse(&input, format).ok())
+        .map(PrimitiveDateTime::assume_utc)
+}
+
+/// Moves the date to the `day` of the next month, the day is clamped to the
+/// length of the month.
+pub fn add_month(date: OffsetDateTime, day: u8) -> Option<OffsetDateTime> {
+    let (year, month) = match date.month() {
+        Month::December => (date.year() + 1, Month::January),
+        month => (date.year(), month.next()),
+    };
+    let day = day.min(month.length(year));
+
+    date.replace_day(1)
+        .and_then(|d| d.replace_year(year))
+        .and_then(|d| d.replace_month(month))
+        .and_then(|d| d.replace_day(day))
+        .ok()
+}
+
+/// Parses time of day in `HH:MM` format.
+pub fn parse_time_of_day(input: &str) -> Option<Time> {
+    Time::parse(input.trim(), &TIME_OF_DAY_FORMAT).ok()
+}
+
+pub fn format_time_of_day(time: Time) -> String {
+    time.format(&TIME_OF_DAY_FORMAT).unwrap()
+}
+
+/// Parses comma separated list of weekdays, full names and three letter
+/// abbreviations are accepted, `none` results in an empty list.
+pub fn parse_weekdays(input: &str) -> Option<Vec<Weekday>> {
+    let input = input.trim().to_lowercase();
+    if input == "none" || input.is_empty() {
+        return Some(vec![]);
+    }
+
+    input
+        .split(',')
+        .map(|day| match day.trim() {
+            "mon" | "monday" => Some(Weekday::Monday),
+            "tue" | "tuesday" => Some(Weekday::Tuesday),
+            "wed" | "wednesday" => Some(Weekday::Wednesday),
+            "thu" | "thursday" => Some(Weekday::Thursday),
+            "fri" | "friday" => Some(Weekday::Friday),
+            "sat" | "saturday" => Some(Weekday::Saturday),
+            "sun" | "sunday" => Some(Weekday::Sunday),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn format_weekdays<'a>(days: impl IntoIterator<Item = &'a Weekday>) -> String {
+    days.into_iter()
+        .sorted_by_key(|day| day.number_days_from_monday())
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::{datetime, time};
+
+    use super::*;
+
+    const NOW: OffsetDateTime = datetime!(2024-05-01 14:30 UTC);
+
+    #[test]
+    fn parse_date_keywords() {
+        assert_eq!(
+            parse_date("today", NOW),
+            Some(datetime!(2024-05-01 0:00 UTC))
+        );
+        assert_eq!(
+            parse_date(" Tomorrow ", NOW),
+            Some(datetime!(2024-05-02 0:00 UTC))
+        );
+    }
+
+    #[test]
+    fn parse_date_relative() {
+        assert_eq!(
+            parse_date("30m", NOW),
+            Some(datetime!(2024-05-01 15:00 UTC))
+        );
+        assert_eq!(
+            parse_date("in 2 hours", NOW),
+            Some(datetime!(2024-05-01 16:30 UTC))
+        );
+        assert_eq!(
+            parse_date("in 3 days", NOW),
+            Some(datetime!(2024-05-04 14:30 UTC))
+        );
+        assert_eq!(
+            parse_date("1 week", NOW),
+            Some(datetime!(2024-05-08 14:30 UTC))
+        );
+        assert_eq!(parse_date("in 3 fortnights", NOW), None);
+    }
+
+    #[test]
+    fn parse_date_relative_overflow() {
+        assert_eq!(parse_date("in 999999999999999999 minutes", NOW), None);
+        assert_eq!(parse_date("in 99999999999999999999 weeks", NOW), None);
+        assert_eq!(parse_date("in 9999999999999 days", NOW), None);
+    }
+
+    #[test]
+    fn parse_date_absolute() {
+        assert_eq!(
+            parse_date("2024-06-01", NOW),
+            Some(datetime!(2024-06-01 0:00 UTC))
+        );
+        assert_eq!(
+            parse_date("2024-06-01 09:15", NOW),
+            Some(datetime!(2024-06-01 9:15 UTC))
+        );
+        assert_eq!(
+            parse_date("2024-06-01 09:15:30", NOW),
+            Some(datetime!(2024-06-01 9:15:30 UTC))
+        );
+        assert_eq!(parse_date("2024-02-30", NOW), None);
+        assert_eq!(parse_date("next friday", NOW), None);
+    }
+
+    #[test]
+    fn parse_time_of_day_formats() {
+        assert_eq!(parse_time_of_day("09:05"), Some(time!(9:05)));
+        assert_eq!(parse_time_of_day(" 23:59 "), Some(time!(23:59)));
+        assert_eq!(parse_time_of_day("24:00"), None);
+        assert_eq!(parse_time_of_day("9"), None);
+    }
+
+    #[test]
+    fn parse_weekdays_names() {
+        assert_eq!(
+            parse_weekdays("sat, Sunday"),
+            Some(vec![Weekday::Saturday, Weekday::Sunday])
+        );
+        assert_eq!(parse_weekdays("none"), Some(vec![]));
+        assert_eq!(parse_weekdays("monkey"), None);
+        assert_eq!(parse_weekdays("fri,sunshine"), None);
+    }
+
+    #[test]
+    fn add_month_regular() {
+        assert_eq!(
+            add_month(datetime!(2024-05-15 10:00 UTC), 15),
+            Some(datetime!(2024-06-15 10:00 UTC))
+        );
+        assert_eq!(
+            add_month(datetime!(2024-12-15 10:00 UTC), 15),
+            Some(datetime!(2025-01-15 10:00 UTC))
+        );
+    }
+
+    #[test]
+    fn add_month_clamps_day() {
+        assert_eq!(
+            add_month(datetime!(2024-01-31 10:00 UTC), 31),
+            Some(datetime!(2024-02-29 10:00 UTC))
+        );
+        assert_eq!(
+            add_month(datetime!(2023-01-31 10:00 UTC), 31),
+            Some(datetime!(2023-02-28 10:00 UTC))
+        );
+    }
+
+    #[test]
+    fn add_month_keeps_day() {
+        assert_eq!(
+            add_month(datetime!(2023-02-28 10:00 UTC), 31),
+            Some(datetime!(2023-03-31 10:00 UTC))
+        );
+        assert_eq!(
+            add_month(datetime!(2023-04-30 10:00 UTC), 30),
+            Some(datetime!(2023-05-30 10:00 UTC))
+        );
+    }
+}